@@ -0,0 +1 @@
+ALTER TABLE classrooms ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
@@ -0,0 +1,9 @@
+ALTER TABLE assignments ADD COLUMN IF NOT EXISTS position INTEGER NOT NULL DEFAULT 0;
+
+UPDATE assignments
+SET position = ordered.rn
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY classroom_id ORDER BY created_at, id) AS rn
+    FROM assignments
+) ordered
+WHERE assignments.id = ordered.id;
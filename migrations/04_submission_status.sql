@@ -0,0 +1,4 @@
+ALTER TABLE submissions ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'turned_in';
+
+UPDATE submissions SET status = 'returned' WHERE is_graded;
+UPDATE submissions SET status = 'assigned' WHERE NOT is_graded AND submitted_at IS NULL;
@@ -0,0 +1,7 @@
+ALTER TABLE assignments ADD COLUMN IF NOT EXISTS accept_late BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE assignments ADD COLUMN IF NOT EXISTS late_penalty_per_day INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE assignments ADD COLUMN IF NOT EXISTS late_cutoff TIMESTAMPTZ;
+
+ALTER TABLE submissions ADD COLUMN IF NOT EXISTS raw_grade INTEGER;
+
+UPDATE submissions SET raw_grade = grade WHERE raw_grade IS NULL;
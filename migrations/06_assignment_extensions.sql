@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS assignment_extensions (
+    assignment_id INTEGER NOT NULL REFERENCES assignments(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    due_date TIMESTAMPTZ,
+    late_cutoff TIMESTAMPTZ,
+    granted_by INTEGER NOT NULL REFERENCES users(id),
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY(assignment_id, user_id)
+);
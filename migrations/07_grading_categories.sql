@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS grading_categories (
+    id SERIAL PRIMARY KEY,
+    classroom_id INTEGER NOT NULL REFERENCES classrooms(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    weight DOUBLE PRECISION NOT NULL,
+    drop_lowest INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE assignments ADD COLUMN IF NOT EXISTS category_id INTEGER REFERENCES grading_categories(id) ON DELETE SET NULL;
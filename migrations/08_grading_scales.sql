@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS grading_scale_bands (
+    id SERIAL PRIMARY KEY,
+    classroom_id INTEGER NOT NULL REFERENCES classrooms(id) ON DELETE CASCADE,
+    label VARCHAR(32) NOT NULL,
+    min_percentage DOUBLE PRECISION NOT NULL,
+    UNIQUE (classroom_id, min_percentage)
+);
+
+INSERT INTO grading_scale_bands (classroom_id, label, min_percentage)
+SELECT c.id, b.label, b.min_percentage
+FROM classrooms c
+CROSS JOIN (VALUES ('A', 90.0), ('B', 80.0), ('C', 70.0), ('D', 60.0), ('F', 0.0)) AS b(label, min_percentage)
+ON CONFLICT DO NOTHING;
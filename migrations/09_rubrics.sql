@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS rubric_criteria (
+    id SERIAL PRIMARY KEY,
+    assignment_id INTEGER NOT NULL REFERENCES assignments(id) ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    position INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS rubric_levels (
+    id SERIAL PRIMARY KEY,
+    criterion_id INTEGER NOT NULL REFERENCES rubric_criteria(id) ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    points INTEGER NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS submission_rubric_scores (
+    submission_id INTEGER NOT NULL REFERENCES submissions(id) ON DELETE CASCADE,
+    criterion_id INTEGER NOT NULL REFERENCES rubric_criteria(id) ON DELETE CASCADE,
+    level_id INTEGER NOT NULL REFERENCES rubric_levels(id) ON DELETE CASCADE,
+    points INTEGER NOT NULL,
+    comment TEXT,
+    PRIMARY KEY (submission_id, criterion_id)
+);
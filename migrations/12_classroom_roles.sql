@@ -0,0 +1,25 @@
+UPDATE user_classroom_roles uc
+SET role = 'creator'
+FROM classrooms c
+WHERE uc.classroom_id = c.id AND uc.user_id = c.creator_id;
+
+CREATE TABLE IF NOT EXISTS classroom_invitations (
+    id SERIAL PRIMARY KEY,
+    classroom_id INTEGER NOT NULL REFERENCES classrooms(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    role TEXT NOT NULL DEFAULT 'teacher',
+    invited_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    responded_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS classroom_invitations_pending_idx
+    ON classroom_invitations (classroom_id, LOWER(email))
+    WHERE status = 'pending';
+
+-- invitations are matched on email, so addresses have to be unique regardless of case;
+-- accounts that only differ in case make this fail and have to be merged by hand first
+UPDATE users SET email = LOWER(email) WHERE email <> LOWER(email);
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_lower_idx ON users (LOWER(email));
@@ -0,0 +1 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS roles_updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,23 @@
+ALTER TABLE classrooms ADD COLUMN IF NOT EXISTS join_code_enabled BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE UNIQUE INDEX IF NOT EXISTS classrooms_join_code_upper_idx ON classrooms (UPPER(join_code));
+
+-- existing classrooms still carry a UUID join code, give them a short one too
+DO $$
+DECLARE
+    alphabet CONSTANT TEXT := 'ABCDEFGHJKLMNPQRSTUVWXYZ23456789';
+    classroom RECORD;
+    code TEXT;
+BEGIN
+    FOR classroom IN SELECT id FROM classrooms WHERE LENGTH(join_code) <> 7 LOOP
+        LOOP
+            code := '';
+            FOR i IN 1..7 LOOP
+                code := code || SUBSTR(alphabet, 1 + FLOOR(RANDOM() * 32)::INT, 1);
+            END LOOP;
+            EXIT WHEN NOT EXISTS (SELECT 1 FROM classrooms WHERE UPPER(join_code) = code);
+        END LOOP;
+
+        UPDATE classrooms SET join_code = code WHERE id = classroom.id;
+    END LOOP;
+END $$;
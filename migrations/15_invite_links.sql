@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS classroom_invite_links (
+    id SERIAL PRIMARY KEY,
+    classroom_id INTEGER NOT NULL REFERENCES classrooms(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    created_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ,
+    max_uses INTEGER,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    allowed_emails TEXT[] NOT NULL DEFAULT '{}',
+    allowed_domain TEXT,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS classroom_invite_links_classroom_idx ON classroom_invite_links (classroom_id);
@@ -0,0 +1,5 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS sourced_id TEXT UNIQUE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE classrooms ADD COLUMN IF NOT EXISTS sourced_id TEXT UNIQUE;
+ALTER TABLE classrooms ADD COLUMN IF NOT EXISTS org_sourced_id TEXT;
@@ -0,0 +1,40 @@
+CREATE TABLE IF NOT EXISTS class_groups (
+    id SERIAL PRIMARY KEY,
+    classroom_id INTEGER NOT NULL REFERENCES classrooms(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS class_groups_name_idx ON class_groups (classroom_id, LOWER(name));
+
+CREATE TABLE IF NOT EXISTS class_group_members (
+    group_id INTEGER NOT NULL REFERENCES class_groups(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS class_group_members_user_idx ON class_group_members (user_id);
+
+-- an assignment without any rows here is assigned to the whole class
+CREATE TABLE IF NOT EXISTS assignment_groups (
+    assignment_id INTEGER NOT NULL REFERENCES assignments(id) ON DELETE CASCADE,
+    group_id INTEGER NOT NULL REFERENCES class_groups(id) ON DELETE CASCADE,
+    PRIMARY KEY (assignment_id, group_id)
+);
+
+CREATE INDEX IF NOT EXISTS assignment_groups_group_idx ON assignment_groups (group_id);
+
+-- the single place that decides whether a student is a target of an assignment
+CREATE OR REPLACE FUNCTION assignment_visible_to(p_assignment_id INTEGER, p_user_id INTEGER)
+RETURNS BOOLEAN
+LANGUAGE SQL STABLE
+AS $$
+    SELECT NOT EXISTS (SELECT 1 FROM assignment_groups ag WHERE ag.assignment_id = p_assignment_id)
+        OR EXISTS (
+            SELECT 1 FROM assignment_groups ag
+            JOIN class_group_members gm ON gm.group_id = ag.group_id
+            WHERE ag.assignment_id = p_assignment_id AND gm.user_id = p_user_id
+        )
+$$;
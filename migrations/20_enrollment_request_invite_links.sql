@@ -0,0 +1,2 @@
+-- a link used to request enrollment is only redeemed once the request is approved
+ALTER TABLE enrollment_requests ADD COLUMN IF NOT EXISTS invite_link_id INTEGER REFERENCES classroom_invite_links(id) ON DELETE SET NULL;
@@ -7,4 +7,16 @@ pub async fn create_pool(database_url: &str) -> PgPool {
         .connect(database_url)
         .await
         .expect("Failed to create pool")
-}
\ No newline at end of file
+}
+
+// emails are unique regardless of case, so this matches one account at most
+pub async fn grant_admin(pool: &PgPool, email: &str) -> Result<Option<i32>, sqlx::Error> {
+    let user = sqlx::query!(
+        "UPDATE users SET is_admin = TRUE WHERE LOWER(email) = LOWER(TRIM($1)) RETURNING id",
+        email
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user.map(|u| u.id))
+}
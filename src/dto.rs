@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::{EnrollmentStatus, GradeBand, InvitationStatus, SubmissionStatus};
+
 #[derive(Serialize, Deserialize)]
 pub struct AuthResponse {
     pub user_id: i32,
@@ -19,6 +21,12 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SetPasswordRequest {
+    pub token: String,
+    pub password: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct EditProfileRequest {
     pub name: String,
@@ -38,6 +46,13 @@ pub struct CreateClassroomRequest {
     pub description: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateClassroomRequest {
+    pub title: String,
+    pub description: String,
+    pub require_approval: Option<bool>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ClassroomListResponse {
     pub id: i32,
@@ -55,6 +70,11 @@ pub struct ClassroomResponse {
     pub assignments: Vec<AssignmentInfo>,
     pub users: Vec<ClassroomUser>,
     pub join_code: String,
+    pub join_code_enabled: bool,
+    pub require_approval: bool,
+    pub is_archived: bool,
+    pub categories: Vec<GradingCategoryInfo>,
+    pub my_grade: Option<CourseGrade>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -68,17 +88,41 @@ pub struct AssignmentInfo {
     pub id: i32,
     pub title: String,
     pub due_date: String,
+    pub category_id: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct SubmissionInfo {
-    pub id: i32,
+    pub id: Option<i32>,
     pub assignment_id: i32,
     pub assignment_title: String,
+    pub user_id: i32,
     pub student_name: String,
     pub submitted_at: Option<String>,
     pub is_graded: bool,
     pub grade: Option<i32>,
+    pub status: SubmissionStatus,
+    pub is_late: bool,
+    pub is_missing: bool,
+    pub comment_count: i64,
+    pub unread_comments: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatusFilter {
+    Assigned,
+    TurnedIn,
+    Returned,
+    Resubmitted,
+    Late,
+    Missing,
+}
+
+#[derive(Deserialize)]
+pub struct SubmissionListQuery {
+    pub status: Option<SubmissionStatusFilter>,
+    pub group_id: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -91,15 +135,25 @@ pub struct SubmissionResponse {
     pub submitted_at: Option<String>,
     pub is_graded: bool,
     pub grade: Option<i32>,
+    pub raw_grade: Option<i32>,
+    pub display_grade: Option<String>,
+    pub feedback: Option<String>,
+    pub status: SubmissionStatus,
+    pub is_late: bool,
+    pub is_missing: bool,
+    pub days_late: i64,
     pub graded_at: Option<String>,
     pub grader_name: Option<String>,
     pub files: Vec<AssignmentFile>,
+    pub rubric: Vec<RubricCriterionInfo>,
+    pub rubric_scores: Vec<RubricScoreInfo>,
 }
 
 #[derive(Deserialize)]
 pub struct GradeSubmissionRequest {
     pub grade: Option<i32>,
-    // pub feedback: Option<String>,
+    pub feedback: Option<String>,
+    pub rubric: Option<Vec<RubricSelection>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -111,10 +165,25 @@ pub struct AssignmentResponse {
     pub description: String,
     pub due_date: String,
     pub points: i32,
+    pub category_id: Option<i32>,
+    pub accept_late: bool,
+    pub late_penalty_per_day: i32,
+    pub late_cutoff: Option<String>,
+    pub has_extension: bool,
     pub materials: Vec<AssignmentFile>,
     pub submission_files: Vec<AssignmentFile>,
+    pub submission_id: Option<i32>,
+    pub unread_comments: i64,
     pub is_submitted: bool,
     pub grade: Option<i32>,
+    pub display_grade: Option<String>,
+    pub feedback: Option<String>,
+    pub status: SubmissionStatus,
+    pub is_late: bool,
+    pub is_missing: bool,
+    pub rubric: Vec<RubricCriterionInfo>,
+    pub rubric_scores: Vec<RubricScoreInfo>,
+    pub group_ids: Vec<i32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -137,6 +206,44 @@ pub struct CreateAssignmentRequest {
     pub description: String,
     pub due_date: Option<String>,
     pub points: i32,
+    pub category_id: Option<i32>,
+    pub accept_late: Option<bool>,
+    pub late_penalty_per_day: Option<i32>,
+    pub late_cutoff: Option<String>,
+    pub group_ids: Option<Vec<i32>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateAssignmentRequest {
+    pub title: String,
+    pub description: String,
+    pub due_date: Option<String>,
+    pub points: i32,
+    pub category_id: Option<i32>,
+    pub accept_late: Option<bool>,
+    pub late_penalty_per_day: Option<i32>,
+    pub late_cutoff: Option<String>,
+    pub group_ids: Option<Vec<i32>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReorderAssignmentsRequest {
+    pub assignment_ids: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GrantExtensionRequest {
+    pub due_date: Option<String>,
+    pub late_cutoff: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExtensionInfo {
+    pub user_id: i32,
+    pub student_name: String,
+    pub due_date: Option<String>,
+    pub late_cutoff: Option<String>,
+    pub granted_at: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -147,9 +254,490 @@ pub struct CreatedAssignmentResponse {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ClassroomRoleResponse {
     pub is_teacher: bool,
+    pub is_creator: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct JoinClassroomRequest {
     pub join_code: String,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateJoinCodeRequest {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JoinCodeResponse {
+    pub join_code: String,
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradebookResponse {
+    pub grading_scale: Vec<GradeBand>,
+    pub categories: Vec<GradingCategoryInfo>,
+    pub assignments: Vec<GradebookAssignment>,
+    pub students: Vec<GradebookStudent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradebookAssignment {
+    pub id: i32,
+    pub title: String,
+    pub due_date: Option<String>,
+    pub points: i32,
+    pub category_id: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradebookStudent {
+    pub user_id: i32,
+    pub name: String,
+    pub email: String,
+    pub grades: Vec<GradebookEntry>,
+    pub earned_points: i64,
+    pub graded_points: i64,
+    pub total_points: i64,
+    pub percentage: Option<f64>,
+    pub weighted_percentage: Option<f64>,
+    pub display_grade: Option<String>,
+    pub category_grades: Vec<CategoryGrade>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradebookEntry {
+    pub assignment_id: i32,
+    pub submission_id: Option<i32>,
+    pub grade: Option<i32>,
+    pub display_grade: Option<String>,
+    pub points: i32,
+    pub status: SubmissionStatus,
+    pub is_late: bool,
+    pub is_missing: bool,
+    pub is_assigned: bool,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum GradebookExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GradebookExportQuery {
+    #[serde(default)]
+    pub format: GradebookExportFormat,
+    pub group_id: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GradebookQuery {
+    pub group_id: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GradeImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradeImportReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub unknown_students: Vec<String>,
+    pub unknown_assignments: Vec<String>,
+    pub errors: Vec<GradeImportError>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradeImportError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradingCategoryRequest {
+    pub name: String,
+    pub weight: f64,
+    pub drop_lowest: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradingCategoryInfo {
+    pub id: i32,
+    pub name: String,
+    pub weight: f64,
+    pub drop_lowest: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CategoryGrade {
+    pub category_id: i32,
+    pub name: String,
+    pub weight: f64,
+    pub percentage: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CourseGrade {
+    pub earned_points: i64,
+    pub graded_points: i64,
+    pub percentage: Option<f64>,
+    pub weighted_percentage: Option<f64>,
+    pub display_grade: Option<String>,
+    pub category_grades: Vec<CategoryGrade>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradingScaleRequest {
+    pub bands: Vec<GradeBand>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GradingScaleResponse {
+    pub bands: Vec<GradeBand>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RubricRequest {
+    pub criteria: Vec<RubricCriterionRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RubricCriterionRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub levels: Vec<RubricLevelRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RubricLevelRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub points: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RubricCriterionInfo {
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub max_points: i32,
+    pub levels: Vec<RubricLevelInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RubricLevelInfo {
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub points: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RubricSelection {
+    pub criterion_id: i32,
+    pub level_id: i32,
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RubricScoreInfo {
+    pub criterion_id: i32,
+    pub level_id: i32,
+    pub points: i32,
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserClassGrades {
+    pub class_id: i32,
+    pub class_title: String,
+    pub teacher: String,
+    pub is_archived: bool,
+    pub total_points: i64,
+    pub grade: CourseGrade,
+    pub assignments: Vec<UserAssignmentGrade>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserAssignmentGrade {
+    pub assignment_id: i32,
+    pub title: String,
+    pub due_date: Option<String>,
+    pub points: i32,
+    pub category_id: Option<i32>,
+    pub grade: Option<i32>,
+    pub display_grade: Option<String>,
+    pub status: SubmissionStatus,
+    pub is_late: bool,
+    pub is_missing: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoState {
+    NotSubmitted,
+    TurnedIn,
+    Graded,
+    Missing,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoFilter {
+    Upcoming,
+    Overdue,
+    Done,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TodoQuery {
+    pub filter: Option<TodoFilter>,
+    pub class_id: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TodoAssignment {
+    pub assignment_id: i32,
+    pub class_id: i32,
+    pub class_title: String,
+    pub title: String,
+    pub due_date: Option<String>,
+    pub points: i32,
+    pub grade: Option<i32>,
+    pub state: TodoState,
+    pub status: SubmissionStatus,
+    pub is_late: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StreamQuery {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StreamResponse {
+    pub announcements: Vec<AnnouncementInfo>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnnouncementInfo {
+    pub id: i32,
+    pub author_id: i32,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub attachments: Vec<AnnouncementAttachment>,
+    pub comments: Vec<CommentInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnnouncementAttachment {
+    pub id: i32,
+    pub file_name: String,
+    pub content_type: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnnouncementRequest {
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreatedAnnouncementResponse {
+    pub id: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommentRequest {
+    pub body: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommentInfo {
+    pub id: i32,
+    pub author_id: i32,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InviteTeacherRequest {
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InvitationInfo {
+    pub id: i32,
+    pub class_id: i32,
+    pub class_title: String,
+    pub email: String,
+    pub invited_by: String,
+    pub status: InvitationStatus,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateInviteLinkRequest {
+    pub expires_at: Option<String>,
+    pub max_uses: Option<i32>,
+    pub allowed_emails: Option<Vec<String>>,
+    pub allowed_domain: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InviteLinkInfo {
+    pub id: i32,
+    pub token: String,
+    pub created_by: String,
+    pub expires_at: Option<String>,
+    pub max_uses: Option<i32>,
+    pub use_count: i32,
+    pub allowed_emails: Vec<String>,
+    pub allowed_domain: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EnrollmentRequestInfo {
+    pub id: i32,
+    pub class_id: i32,
+    pub class_title: String,
+    pub user_id: i32,
+    pub user_name: String,
+    pub email: String,
+    pub status: EnrollmentStatus,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberRole {
+    Teacher,
+    Student,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MembershipQuery {
+    #[serde(default)]
+    pub purge: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateMemberRoleRequest {
+    pub role: MemberRole,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RosterImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RosterRowStatus {
+    Created,
+    Enrolled,
+    AlreadyEnrolled,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RosterImportRow {
+    pub row: usize,
+    pub email: String,
+    pub role: Option<MemberRole>,
+    pub status: RosterRowStatus,
+    pub message: Option<String>,
+    pub setup_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RosterImportReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub total_rows: usize,
+    pub created: usize,
+    pub enrolled: usize,
+    pub already_enrolled: usize,
+    pub errors: usize,
+    pub rows: Vec<RosterImportRow>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OneRosterImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OneRosterImportError {
+    pub file: String,
+    pub row: usize,
+    pub sourced_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PasswordSetupLink {
+    pub sourced_id: String,
+    pub email: String,
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OneRosterImportReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub orgs: usize,
+    pub users_created: usize,
+    pub users_updated: usize,
+    pub classes_created: usize,
+    pub classes_updated: usize,
+    pub classes_archived: usize,
+    pub enrollments_saved: usize,
+    pub enrollments_removed: usize,
+    pub skipped: usize,
+    pub errors: Vec<OneRosterImportError>,
+    pub password_setup: Vec<PasswordSetupLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupMembersRequest {
+    pub user_ids: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupMember {
+    pub user_id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupInfo {
+    pub id: i32,
+    pub name: String,
+    pub members: Vec<GroupMember>,
+    pub assignment_count: i64,
+}
@@ -5,11 +5,13 @@ use axum::{
     Json,
 };
 use bcrypt::{hash, verify, DEFAULT_COST};
-use sqlx::PgPool;
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
 use tower_cookies::{Cookie, Cookies};
+use uuid::Uuid;
 
 use crate::models::{UserClassroomRole, ClassroomRole};
-use crate::dto::{AuthResponse, LoginRequest, RegisterRequest};
+use crate::dto::{AuthResponse, LoginRequest, RegisterRequest, SetPasswordRequest};
 use crate::middlewares::jwt::{create_jwt, set_auth_cookie};
 
 pub async fn register(
@@ -18,9 +20,12 @@ pub async fn register(
     Json(request): Json<RegisterRequest>
 ) -> Result<Json<AuthResponse>, (StatusCode, String)> {
 
+    // invitations are matched on email, so an address may only belong to one account whatever its case
+    let email = request.email.trim().to_lowercase();
+
     let existing_user = sqlx::query!(
-        "SELECT email FROM users WHERE email = $1",
-        request.email
+        "SELECT email FROM users WHERE LOWER(email) = $1",
+        email
     )
     .fetch_optional(&pool)
     .await
@@ -40,7 +45,7 @@ pub async fn register(
          VALUES ($1, $2, $3)
          RETURNING id, name, email",
         request.name,
-        request.email,
+        email,
         hashed_password
     )
     .fetch_one(&pool)
@@ -65,7 +70,7 @@ pub async fn login(
 ) -> Result<Json<AuthResponse>, (StatusCode, String)> {
 
     let user = sqlx::query!(
-        "SELECT id, name, email, password_hash FROM users WHERE email = $1",
+        "SELECT id, name, email, password_hash FROM users WHERE LOWER(email) = LOWER(TRIM($1))",
         request.email
     )
     .fetch_optional(&pool)
@@ -76,6 +81,11 @@ pub async fn login(
         Some(user) => user,
         None => return Err((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string())),
     };
+
+    // accounts created by a roster import have no password until the setup link is used
+    if user.password_hash.is_empty() {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()));
+    }
     
     let password_matches = verify(request.password, &user.password_hash)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -84,23 +94,34 @@ pub async fn login(
         return Err((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()));
     }
 
+    refresh_auth_cookie(&pool, &cookies, user.id).await?;
+
+    Ok(Json(AuthResponse {
+        user_id: user.id,
+        email: user.email,
+    }))
+}
+
+// reissues the token so the classroom roles cached in its claims match the database again
+pub(crate) async fn refresh_auth_cookie(
+    pool: &PgPool,
+    cookies: &Cookies,
+    user_id: i32,
+) -> Result<(), (StatusCode, String)> {
     let classroom_roles = sqlx::query_as!(UserClassroomRole, 
         "SELECT user_id, classroom_id, role AS \"role: ClassroomRole\", joined_at FROM user_classroom_roles WHERE user_id = $1", 
-        user.id
+        user_id
     )
-    .fetch_all(&pool)
+    .fetch_all(pool)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
-    let token = create_jwt(user.id, classroom_roles)
+    let token = create_jwt(user_id, classroom_roles)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
     cookies.add(set_auth_cookie(&token));
 
-    Ok(Json(AuthResponse {
-        user_id: user.id,
-        email: user.email,
-    }))
+    Ok(())
 }
 
 pub async fn logout(cookies: Cookies) -> impl IntoResponse {
@@ -109,4 +130,99 @@ pub async fn logout(cookies: Cookies) -> impl IntoResponse {
     cookie.set_max_age(tower_cookies::cookie::time::Duration::seconds(0));
     cookies.add(cookie);
     (StatusCode::OK, "Logged out successfully".to_string())
-}
\ No newline at end of file
+}
+
+const PASSWORD_SETUP_DAYS: i64 = 7;
+
+pub(crate) async fn issue_password_setup_token(
+    conn: &mut PgConnection,
+    user_id: i32,
+    created_by: i32,
+) -> Result<String, (StatusCode, String)> {
+    let token = Uuid::new_v4().simple().to_string();
+
+    // a reissued link replaces any earlier one that wasn't used
+    sqlx::query!(
+        "UPDATE password_setup_tokens SET expires_at = NOW() WHERE user_id = $1 AND used_at IS NULL AND expires_at > NOW()",
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO password_setup_tokens (token_hash, user_id, created_by, expires_at)
+        VALUES (encode(sha256(convert_to($1, 'UTF8')), 'hex'), $2, $3, $4)
+        "#,
+        token,
+        user_id,
+        created_by,
+        Utc::now() + Duration::days(PASSWORD_SETUP_DAYS)
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(token)
+}
+
+pub async fn set_password(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Json(request): Json<SetPasswordRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    if request.password.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Password must not be empty".to_string()));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let setup = sqlx::query!(
+        r#"
+        UPDATE password_setup_tokens SET used_at = NOW()
+        WHERE token_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex') AND used_at IS NULL AND expires_at > NOW()
+        RETURNING user_id
+        "#,
+        request.token.trim()
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let setup = match setup {
+        Some(setup) => setup,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                "Password setup link is invalid or has expired".to_string(),
+            ))
+        }
+    };
+
+    let hashed_password = hash(request.password, DEFAULT_COST)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let user = sqlx::query!(
+        "UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2 RETURNING id, email",
+        hashed_password,
+        setup.user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    refresh_auth_cookie(&pool, &cookies, user.id).await?;
+
+    Ok(Json(AuthResponse {
+        user_id: user.id,
+        email: user.email,
+    }))
+}
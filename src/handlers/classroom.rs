@@ -1,20 +1,152 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Write};
 
 use axum::body::Body;
-use axum::extract::Path;
+use axum::extract::{Path, Query};
 use axum::http::{HeaderMap, Response, header};
 use axum::response::IntoResponse;
 use axum::{Json, extract::Multipart, extract::State, http::StatusCode};
 use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
-use sqlx::PgPool;
+use serde::Deserialize;
+use sqlx::{PgConnection, PgExecutor, PgPool};
 use tower_cookies::Cookies;
 use uuid::Uuid;
 
 use crate::dto::{
-    AssignmentFile, AssignmentInfo, AssignmentResponse, ClassroomResponse, ClassroomRoleResponse, ClassroomUser, CreateAssignmentRequest, CreateClassroomRequest, CreatedAssignmentResponse, GradeSubmissionRequest, JoinClassroomRequest, SubmissionInfo, SubmissionResponse
+    AssignmentFile, AssignmentInfo, CategoryGrade, CommentInfo, CommentRequest, CourseGrade, ExtensionInfo, GradebookAssignment, GradebookEntry, GradebookExportFormat, GradebookExportQuery, GradebookQuery, GradeImportError, GradeImportQuery, GradeImportReport, GradebookResponse, GradebookStudent, GradingCategoryInfo, GradingCategoryRequest, GradingScaleRequest, GradingScaleResponse, GrantExtensionRequest, AssignmentResponse, ClassroomResponse, ClassroomRoleResponse, ClassroomUser, CreateAssignmentRequest, CreateClassroomRequest, CreatedAssignmentResponse, GradeSubmissionRequest, JoinClassroomRequest, JoinCodeResponse, ReorderAssignmentsRequest, RubricCriterionInfo, RubricLevelInfo, RubricRequest, RubricScoreInfo, RubricSelection, SubmissionInfo, SubmissionListQuery, SubmissionResponse, SubmissionStatusFilter, UpdateAssignmentRequest, UpdateClassroomRequest, UpdateJoinCodeRequest
 };
+use crate::handlers::auth::refresh_auth_cookie;
+use crate::handlers::groups::{check_assignment_visible, check_group, load_assignment_groups, save_assignment_groups, validate_assignment_groups};
+use crate::handlers::members::{find_invite_link, redeem_invite_link, reserve_invite_link};
 use crate::middlewares::jwt::check_auth;
+use crate::models::{generate_join_code, weighted_percentage, ClassroomRole, EnrollmentStatus, GradeBand, GradingCategory, GradingScale, LatePolicy, ScoredAssignment, SubmissionStatus};
+
+pub(crate) async fn classroom_role(
+    pool: &PgPool,
+    classroom_id: i32,
+    user_id: i32,
+) -> Result<ClassroomRole, (StatusCode, String)> {
+    let role = sqlx::query!(
+        r#"SELECT role AS "role: ClassroomRole" FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2"#,
+        classroom_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    match role {
+        Some(role) => Ok(role.role),
+        None => Err((
+            StatusCode::FORBIDDEN,
+            "User is not enrolled in this classroom".to_string(),
+        )),
+    }
+}
+
+pub(crate) async fn check_not_archived(pool: &PgPool, classroom_id: i32) -> Result<(), (StatusCode, String)> {
+    let classroom = sqlx::query!(
+        "SELECT archived_at FROM classrooms WHERE id = $1",
+        classroom_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    match classroom {
+        Some(classroom) if classroom.archived_at.is_some() => Err((
+            StatusCode::FORBIDDEN,
+            "Classroom is archived and read-only".to_string(),
+        )),
+        Some(_) => Ok(()),
+        None => Err((StatusCode::NOT_FOUND, "Classroom not found".to_string())),
+    }
+}
+
+pub(crate) fn parse_datetime_input(input: &Option<String>) -> Result<Option<DateTime<Utc>>, (StatusCode, String)> {
+    match input {
+        Some(date_str) => {
+            // "2025-06-07T14:30"
+            let naive = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M").map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid datetime format: {}", e),
+                )
+            })?;
+            Ok(Some(TimeZone::from_utc_datetime(&Utc, &naive)))
+        }
+        None => Ok(None),
+    }
+}
+
+async fn effective_late_policy(
+    pool: &PgPool,
+    classroom_id: i32,
+    assignment_id: i32,
+    user_id: i32,
+) -> Result<Option<LatePolicy>, (StatusCode, String)> {
+    let assignment = sqlx::query!(
+        r#"
+        SELECT
+            a.due_date,
+            a.accept_late,
+            a.late_penalty_per_day,
+            a.late_cutoff,
+            ext.due_date AS "extended_due_date?",
+            ext.late_cutoff AS "extended_cutoff?"
+        FROM assignments a
+        LEFT JOIN assignment_extensions ext ON ext.assignment_id = a.id AND ext.user_id = $3
+        WHERE a.id = $1 AND a.classroom_id = $2
+        "#,
+        assignment_id,
+        classroom_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(assignment.map(|a| {
+        LatePolicy {
+            due_date: a.due_date,
+            accept_late: a.accept_late,
+            penalty_per_day: a.late_penalty_per_day,
+            cutoff: a.late_cutoff,
+        }
+        .with_extension(a.extended_due_date, a.extended_cutoff)
+    }))
+}
+
+fn parse_late_policy(
+    due_date: Option<DateTime<Utc>>,
+    accept_late: Option<bool>,
+    late_penalty_per_day: Option<i32>,
+    late_cutoff: &Option<String>,
+) -> Result<LatePolicy, (StatusCode, String)> {
+    let policy = LatePolicy {
+        due_date,
+        accept_late: accept_late.unwrap_or(true),
+        penalty_per_day: late_penalty_per_day.unwrap_or(0),
+        cutoff: parse_datetime_input(late_cutoff)?,
+    };
+
+    if !(0..=100).contains(&policy.penalty_per_day) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Late penalty must be between 0 and 100 percent per day".to_string(),
+        ));
+    }
+
+    if matches!((policy.due_date, policy.cutoff), (Some(due_date), Some(cutoff)) if cutoff < due_date) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Late cutoff cannot be before the due date".to_string(),
+        ));
+    }
+
+    Ok(policy)
+}
 
 pub async fn create_class(
     State(pool): State<PgPool>,
@@ -34,6 +166,12 @@ pub async fn create_class(
     };
 
     let unique_string_id = uuid::Uuid::new_v4().to_string();
+    let join_code = unique_join_code(&pool).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     let classroom = sqlx::query!(
         "INSERT INTO classrooms (id_base64, name, description, join_code, creator_id)
@@ -42,23 +180,30 @@ pub async fn create_class(
         unique_string_id,
         request.title,
         request.description,
-        unique_string_id,
+        join_code,
         claims.sub
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     sqlx::query!(
         "INSERT INTO user_classroom_roles (classroom_id, user_id, role)
-        VALUES ($1, $2, 'teacher')",
+        VALUES ($1, $2, $3)",
         classroom.id,
-        claims.sub
+        claims.sub,
+        ClassroomRole::Creator.as_str()
     )
-    .execute(&pool)
+    .execute(&mut *tx)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    insert_grading_scale(&mut *tx, classroom.id, &GradingScale::default()).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
     Ok("Classroom created successfully".to_string())
 }
 
@@ -80,7 +225,7 @@ pub async fn get_class(
     };
 
     let classroom = sqlx::query!(
-        r#"SELECT c.id, c.join_code, c.name as title, c.description, u.name as teacher FROM classrooms c JOIN users u ON c.creator_id = u.id WHERE c.id = $1"#,
+        r#"SELECT c.id, c.join_code, c.join_code_enabled, c.require_approval, c.name as title, c.description, c.archived_at, u.name as teacher FROM classrooms c JOIN users u ON c.creator_id = u.id WHERE c.id = $1"#,
         id
     )
     .fetch_optional(&pool)
@@ -92,9 +237,25 @@ pub async fn get_class(
         None => return Err((StatusCode::NOT_FOUND, "Classroom not found".to_string())),
     };
 
+    let is_student = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'student'",
+        classroom.id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    // students only see assignments aimed at the whole class or at one of their groups
     let assignments = sqlx::query!(
-        "SELECT id, title, due_date FROM assignments WHERE classroom_id = $1",
-        classroom.id
+        r#"
+        SELECT a.id, a.title, a.due_date, a.category_id FROM assignments a
+        WHERE a.classroom_id = $1 AND ($2::INT IS NULL OR assignment_visible_to(a.id, $2))
+        ORDER BY a.position, a.id
+        "#,
+        classroom.id,
+        is_student.then_some(claims.sub)
     )
     .fetch_all(&pool)
     .await
@@ -108,9 +269,55 @@ pub async fn get_class(
             due_date: a
                 .due_date
                 .map_or_else(|| "No due date".to_string(), |date| date.to_string()),
+            category_id: a.category_id,
+        })
+        .collect();
+
+    let categories = load_categories(&pool, classroom.id).await?;
+
+    let my_grade = if is_student {
+        let scores: Vec<ScoredAssignment> = sqlx::query!(
+            r#"
+            SELECT a.category_id, a.points, s.grade AS "grade!"
+            FROM submissions s
+            JOIN assignments a ON s.assignment_id = a.id
+            WHERE a.classroom_id = $1 AND s.user_id = $2 AND s.grade IS NOT NULL
+            AND assignment_visible_to(a.id, $2)
+            "#,
+            classroom.id,
+            claims.sub
+        )
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .map(|s| ScoredAssignment {
+            category_id: s.category_id,
+            grade: s.grade,
+            points: s.points.unwrap_or(0),
         })
         .collect();
 
+        let earned_points: i64 = scores.iter().map(|s| s.grade as i64).sum();
+        let graded_points: i64 = scores.iter().map(|s| s.points as i64).sum();
+        let percentage = (graded_points > 0).then(|| 100.0 * earned_points as f64 / graded_points as f64);
+        let weighted_percentage = weighted_percentage(&categories, &scores);
+        let grading_scale = load_grading_scale(&pool, classroom.id).await?;
+
+        Some(CourseGrade {
+            earned_points,
+            graded_points,
+            percentage,
+            weighted_percentage,
+            display_grade: weighted_percentage
+                .or(percentage)
+                .and_then(|p| grading_scale.label_for(p)),
+            category_grades: category_grades(&categories, &scores),
+        })
+    } else {
+        None
+    };
+
     let users = sqlx::query!(
         "SELECT u.name, uc.role as role FROM users u JOIN user_classroom_roles uc ON u.id = uc.user_id WHERE uc.classroom_id = $1",
         classroom.id
@@ -134,17 +341,23 @@ pub async fn get_class(
             .description
             .unwrap_or_else(|| "No description".to_string()),
         teacher: classroom.teacher,
-        assignments: assignments,
-        users: users,
+        assignments,
+        users,
         join_code: classroom.join_code,
+        join_code_enabled: classroom.join_code_enabled,
+        require_approval: classroom.require_approval,
+        is_archived: classroom.archived_at.is_some(),
+        categories: category_infos(&categories),
+        my_grade,
     }))
 }
 
-pub async fn get_class_role(
+pub async fn update_class(
     State(pool): State<PgPool>,
     cookies: Cookies,
     Path(id): Path<i32>,
-) -> Result<Json<ClassroomRoleResponse>, (StatusCode, String)> {
+    Json(request): Json<UpdateClassroomRequest>,
+) -> Result<String, (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
     let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
@@ -157,42 +370,43 @@ pub async fn get_class_role(
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
-    let classroom = sqlx::query!(
-        r#"SELECT c.name FROM classrooms c JOIN users u ON c.creator_id = u.id WHERE c.id = $1"#,
-        id
-    )
-    .fetch_optional(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let classroom = sqlx::query!("SELECT creator_id FROM classrooms WHERE id = $1", id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let _classroom = match classroom {
+    let classroom = match classroom {
         Some(classroom) => classroom,
         None => return Err((StatusCode::NOT_FOUND, "Classroom not found".to_string())),
     };
 
-    let is_enrolled = sqlx::query!(
-        "SELECT role FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
-        id,
-        claims.sub
+    if classroom.creator_id != claims.sub {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only the classroom creator can edit this classroom".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, id).await?;
+
+    sqlx::query!(
+        "UPDATE classrooms SET name = $1, description = $2, require_approval = COALESCE($3, require_approval), updated_at = NOW() WHERE id = $4",
+        request.title,
+        request.description,
+        request.require_approval,
+        id
     )
-    .fetch_optional(&pool)
+    .execute(&pool)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let is_enrolled = match is_enrolled {
-        Some(is_enrolled) => is_enrolled,
-        None => return Err((StatusCode::NOT_FOUND, "User is not enrolled in this classroom".to_string())),
-    };
-
-    Ok(Json(ClassroomRoleResponse {
-        is_teacher: is_enrolled.role == "teacher",
-    }))
+    Ok("Classroom updated successfully".to_string())
 }
 
-pub async fn join_class(
+pub async fn delete_class(
     State(pool): State<PgPool>,
     cookies: Cookies,
-    Json(request): Json<JoinClassroomRequest>,
+    Path(id): Path<i32>,
 ) -> Result<String, (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
@@ -206,55 +420,96 @@ pub async fn join_class(
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
-    let classroom = sqlx::query!(
-        r#"SELECT c.id FROM classrooms c JOIN users u ON c.creator_id = u.id WHERE c.join_code = $1"#,
-        request.join_code
-    )
-    .fetch_optional(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let classroom = sqlx::query!("SELECT creator_id FROM classrooms WHERE id = $1", id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     let classroom = match classroom {
         Some(classroom) => classroom,
         None => return Err((StatusCode::NOT_FOUND, "Classroom not found".to_string())),
     };
 
-    let is_enrolled = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
-        classroom.id,
-        claims.sub
-    )
-    .fetch_optional(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
-
-    if is_enrolled {
+    if classroom.creator_id != claims.sub {
         return Err((
             StatusCode::FORBIDDEN,
-            "User is already enrolled in this classroom".to_string(),
+            "Only the classroom creator can delete this classroom".to_string(),
         ));
     }
 
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let files = sqlx::query!(
+        r#"
+        SELECT af.file_path AS "file_path!" FROM assignment_files af
+        JOIN assignments a ON af.assignment_id = a.id
+        WHERE a.classroom_id = $1
+        UNION ALL
+        SELECT nf.file_path FROM announcement_files nf
+        JOIN announcements an ON nf.announcement_id = an.id
+        WHERE an.classroom_id = $1
+        "#,
+        id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // assignment_files has no ON DELETE CASCADE, so it has to go before the assignments
     sqlx::query!(
-        "INSERT INTO user_classroom_roles (user_id, classroom_id, role) VALUES ($1, $2, $3)",
-        claims.sub,
-        classroom.id,
-        "student"
+        "DELETE FROM assignment_files WHERE assignment_id IN (SELECT id FROM assignments WHERE classroom_id = $1)",
+        id
     )
-    .execute(&pool)
+    .execute(&mut *tx)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok("User was joined to classroom".to_string())
+    // assignments, submissions, announcements and user_classroom_roles are removed by cascade
+    sqlx::query!("DELETE FROM classrooms WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for file in files {
+        let _ = std::fs::remove_file(file.file_path);
+    }
+
+    Ok("Classroom deleted successfully".to_string())
 }
 
-pub async fn create_assignment(
+pub async fn archive_class(
     State(pool): State<PgPool>,
     cookies: Cookies,
     Path(id): Path<i32>,
-    Json(request): Json<CreateAssignmentRequest>,
-) -> Result<Json<CreatedAssignmentResponse>, (StatusCode, String)> {
+) -> Result<String, (StatusCode, String)> {
+    set_class_archived(pool, cookies, id, true).await?;
+
+    Ok("Classroom archived successfully".to_string())
+}
+
+pub async fn unarchive_class(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(id): Path<i32>,
+) -> Result<String, (StatusCode, String)> {
+    set_class_archived(pool, cookies, id, false).await?;
+
+    Ok("Classroom restored successfully".to_string())
+}
+
+async fn set_class_archived(
+    pool: PgPool,
+    cookies: Cookies,
+    id: i32,
+    archived: bool,
+) -> Result<(), (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
     let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
@@ -267,60 +522,40 @@ pub async fn create_assignment(
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
-    let is_teacher = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'teacher'",
-        id,
-        claims.sub
-    )
-    .fetch_optional(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
-
-    if !is_teacher {
-        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
-    }
+    let classroom = sqlx::query!("SELECT creator_id FROM classrooms WHERE id = $1", id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let parsed_due_date: Option<DateTime<Utc>> = match &request.due_date {
-        Some(date_str) => {
-            // "2025-06-07T14:30"
-            let naive = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%dT%H:%M").map_err(|e| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    format!("Invalid datetime format: {}", e),
-                )
-            })?;
-            Some(TimeZone::from_utc_datetime(&Utc, &naive))
-        }
-        None => None,
+    let classroom = match classroom {
+        Some(classroom) => classroom,
+        None => return Err((StatusCode::NOT_FOUND, "Classroom not found".to_string())),
     };
 
-    let unique_string_id = uuid::Uuid::new_v4().to_string();
+    if classroom.creator_id != claims.sub {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only the classroom creator can archive this classroom".to_string(),
+        ));
+    }
 
-    let created_assignment = sqlx::query!(
-        "INSERT INTO assignments (id_base64, classroom_id, title, description, due_date, points, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
-        unique_string_id,
-        id,
-        request.title,
-        request.description,
-        parsed_due_date,
-        request.points,
-        claims.sub
+    sqlx::query!(
+        "UPDATE classrooms SET archived_at = CASE WHEN $1 THEN NOW() ELSE NULL END, updated_at = NOW() WHERE id = $2",
+        archived,
+        id
     )
-    .fetch_optional(&pool)
+    .execute(&pool)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok(Json(CreatedAssignmentResponse {
-        id: created_assignment.unwrap().id,
-    }))
+    Ok(())
 }
 
-pub async fn get_assignment(
+pub async fn get_class_role(
     State(pool): State<PgPool>,
     cookies: Cookies,
-    Path((c_id, a_id)): Path<(i32, i32)>,
-) -> Result<Json<AssignmentResponse>, (StatusCode, String)> {
+    Path(id): Path<i32>,
+) -> Result<Json<ClassroomRoleResponse>, (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
     let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
@@ -333,152 +568,92 @@ pub async fn get_assignment(
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
-    let is_enrolled = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
-        c_id,
-        claims.sub
+    let classroom = sqlx::query!(
+        r#"SELECT c.name FROM classrooms c JOIN users u ON c.creator_id = u.id WHERE c.id = $1"#,
+        id
     )
     .fetch_optional(&pool)
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    if !is_enrolled {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "User is not enrolled in this classroom".to_string(),
-        ));
-    }
-
-    let assignment = sqlx::query!(
-        r#"
-    SELECT 
-        assignments.id,
-        assignments.classroom_id,
-        assignments.title,
-        assignments.description,
-        assignments.due_date,
-        assignments.points,
-        classrooms.name AS class_title
-    FROM assignments
-    JOIN classrooms ON assignments.classroom_id = classrooms.id
-    WHERE assignments.classroom_id = $1 AND assignments.id = $2
-    "#,
-        c_id,
-        a_id
-    )
-    .fetch_optional(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    let assignment = match assignment {
-        Some(assignment) => assignment,
-        None => return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string())),
+    let _classroom = match classroom {
+        Some(classroom) => classroom,
+        None => return Err((StatusCode::NOT_FOUND, "Classroom not found".to_string())),
     };
 
-    let materials = sqlx::query!(
-        "SELECT id, file_name, content_type, assignment_file_type FROM assignment_files WHERE assignment_id = $1 AND assignment_file_type = 'material'",
-        a_id
-    )
-    .fetch_all(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    let materials: Vec<AssignmentFile> = materials
-        .into_iter()
-        .map(|m| AssignmentFile {
-            id: m.id,
-            file_name: m.file_name,
-            content_type: m.content_type.unwrap_or("unknown".to_string()),
-            file_type: m.assignment_file_type.unwrap_or("unknown".to_string()),
-        })
-        .collect();
-
-    let submission_files = sqlx::query!(
-        "SELECT id, file_name, content_type, assignment_file_type FROM assignment_files WHERE assignment_id = $1 AND user_id = $2 AND assignment_file_type = 'submission'",
-        a_id,
-        claims.sub
-    )
-    .fetch_all(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-
-    let submission_files: Vec<AssignmentFile> = submission_files
-        .into_iter()
-        .map(|m| AssignmentFile {
-            id: m.id,
-            file_name: m.file_name,
-            content_type: m.content_type.unwrap_or("unknown".to_string()),
-            file_type: m.assignment_file_type.unwrap_or("unknown".to_string()),
-        })
-        .collect();
-
-    let is_submitted = sqlx::query!(
-        "SELECT 1 as one FROM submissions WHERE assignment_id = $1 AND user_id = $2 AND submitted_at IS NOT NULL",
-        a_id,
+    let is_enrolled = sqlx::query!(
+        r#"SELECT role AS "role: ClassroomRole" FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2"#,
+        id,
         claims.sub
     )
     .fetch_optional(&pool)
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
-
-    let grade: Option<i32>;
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    if is_submitted {
-        grade = sqlx::query!(
-            "SELECT grade FROM submissions WHERE assignment_id = $1 AND user_id = $2",
-            a_id,
-            claims.sub
-        )
-        .fetch_optional(&pool)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .and_then(|s| s.grade);
-    } else {
-        grade = None;
+    let is_enrolled = match is_enrolled {
+        Some(is_enrolled) => is_enrolled,
+        None => return Err((StatusCode::NOT_FOUND, "User is not enrolled in this classroom".to_string())),
     };
 
-    Ok(Json(AssignmentResponse {
-        id: assignment.id,
-        class_id: assignment.classroom_id,
-        title: assignment.title,
-        class_title: assignment.class_title,
-        description: assignment
-            .description
-            .unwrap_or_else(|| "No description".to_string()),
-        due_date: assignment
-            .due_date
-            .map_or_else(|| "No due date".to_string(), |date| date.to_string()),
-        points: assignment.points.unwrap_or(0),
-        materials: materials,
-        submission_files: submission_files,
-        is_submitted: is_submitted,
-        grade: grade,
+    Ok(Json(ClassroomRoleResponse {
+        is_teacher: is_enrolled.role.is_teacher(),
+        is_creator: is_enrolled.role == ClassroomRole::Creator,
     }))
 }
 
-pub async fn save_submission_multipart_files(
+pub async fn join_class(
     State(pool): State<PgPool>,
     cookies: Cookies,
-    Path((c_id, a_id)): Path<(i32, i32)>,
-    mut multipart: Multipart,
-) -> Result<String, (StatusCode, String)> {
-    let claims = check_auth(cookies).await?;
+    Json(request): Json<JoinClassroomRequest>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let claims = check_auth(cookies.clone()).await?;
 
-    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+    let user = sqlx::query!("SELECT id, email FROM users WHERE id = $1", claims.sub)
         .fetch_optional(&pool)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let _user = match user {
+    let user = match user {
         Some(user) => user,
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
+    let classroom = sqlx::query!(
+        r#"SELECT c.id, c.join_code_enabled FROM classrooms c JOIN users u ON c.creator_id = u.id WHERE UPPER(c.join_code) = UPPER(TRIM($1))"#,
+        request.join_code
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // anything that isn't a classroom code may still be an invite link token
+    let (classroom_id, invite_link_id) = match classroom {
+        Some(classroom) => {
+            if !classroom.join_code_enabled {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "Joining with a code is disabled for this classroom".to_string(),
+                ));
+            }
+            (classroom.id, None)
+        }
+        None => match find_invite_link(&pool, &request.join_code).await? {
+            Some(found) => {
+                found
+                    .link
+                    .check(&user.email, Utc::now())
+                    .map_err(|e| (StatusCode::FORBIDDEN, e.to_string()))?;
+                (found.classroom_id, Some(found.id))
+            }
+            None => return Err((StatusCode::NOT_FOUND, "Classroom not found".to_string())),
+        },
+    };
+
+    check_not_archived(&pool, classroom_id).await?;
+
     let is_enrolled = sqlx::query!(
         "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
-        c_id,
+        classroom_id,
         claims.sub
     )
     .fetch_optional(&pool)
@@ -486,89 +661,116 @@ pub async fn save_submission_multipart_files(
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
     .is_some();
 
-    if !is_enrolled {
+    if is_enrolled {
         return Err((
             StatusCode::FORBIDDEN,
-            "User is not enrolled in this classroom".to_string(),
+            "User is already enrolled in this classroom".to_string(),
         ));
     }
 
-    while let Some(mut field) = multipart.next_field().await.unwrap() {
-        let _name = field.name().unwrap().to_string();
-        let filename = field.file_name().unwrap_or("unknown").to_string();
-        let content_type = field
-            .content_type()
-            .map_or("unknown".to_string(), |ct| ct.to_string());
-        let data = field.bytes().await.unwrap();
+    let classroom = sqlx::query!("SELECT require_approval FROM classrooms WHERE id = $1", classroom_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-        let unique_name = format!("{}_{}", Uuid::new_v4(), filename);
-        let path = format!("./uploads/{}", unique_name);
+    if classroom.require_approval {
+        let is_pending = sqlx::query!(
+            "SELECT 1 as one FROM enrollment_requests WHERE classroom_id = $1 AND user_id = $2 AND status = $3",
+            classroom_id,
+            claims.sub,
+            EnrollmentStatus::Pending.as_str()
+        )
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .is_some();
 
-        let mut file =
-            File::create(&path).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-        file.write_all(&data)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if is_pending {
+            return Err((
+                StatusCode::CONFLICT,
+                "An enrollment request for this classroom is already pending".to_string(),
+            ));
+        }
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if classroom.require_approval {
+        // the link is redeemed on approval so a rejected request doesn't use it up
+        if let Some(link_id) = invite_link_id {
+            reserve_invite_link(&mut tx, link_id).await?;
+        }
 
         sqlx::query!(
-            "INSERT INTO assignment_files (assignment_id, user_id, file_name, file_path, content_type, assignment_file_type)
-             VALUES ($1, $2, $3, $4, $5, $6)",
-            a_id,
+            "INSERT INTO enrollment_requests (classroom_id, user_id, status, invite_link_id) VALUES ($1, $2, $3, $4)",
+            classroom_id,
             claims.sub,
-            filename,
-            path,
-            content_type,
-            "submission"
+            EnrollmentStatus::Pending.as_str(),
+            invite_link_id
         )
-        .execute(&pool)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| {
-            let _ = std::fs::remove_file(&path);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-        })?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-        println!("Length of `{}` is {} bytes", filename, data.len());
+        tx.commit()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        return Ok((
+            StatusCode::ACCEPTED,
+            "Enrollment request sent to the teachers for approval".to_string(),
+        ));
     }
 
-    let is_resubmitting = sqlx::query!(
-        "SELECT 1 as one FROM submissions WHERE assignment_id = $1 AND user_id = $2",
-        a_id,
+    if let Some(link_id) = invite_link_id {
+        redeem_invite_link(&mut tx, link_id).await?;
+    }
+
+    sqlx::query!(
+        "INSERT INTO user_classroom_roles (user_id, classroom_id, role) VALUES ($1, $2, $3)",
         claims.sub,
+        classroom_id,
+        "student"
     )
-    .fetch_optional(&pool)
+    .execute(&mut *tx)
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    if !is_resubmitting {
-        sqlx::query!(
-            "INSERT INTO submissions (assignment_id, user_id, is_graded, submitted_at)
-            VALUES ($1, $2, $3, NOW())",
-            a_id,
-            claims.sub,
-            false
-        )
-        .execute(&pool)
+    tx.commit()
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    } else {
-        sqlx::query!(
-            "UPDATE submissions SET submitted_at = NOW() WHERE assignment_id = $1 AND user_id = $2",
-            a_id,
-            claims.sub,
+
+    refresh_auth_cookie(&pool, &cookies, claims.sub).await?;
+
+    Ok((StatusCode::OK, "User was joined to classroom".to_string()))
+}
+
+pub(crate) async fn unique_join_code(pool: &PgPool) -> Result<String, (StatusCode, String)> {
+    loop {
+        let code = generate_join_code();
+        let taken = sqlx::query!(
+            "SELECT 1 as one FROM classrooms WHERE UPPER(join_code) = $1",
+            code
         )
-        .execute(&pool)
+        .fetch_optional(pool)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    }
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .is_some();
 
-    Ok("Submission saved successfully".to_string())
+        if !taken {
+            return Ok(code);
+        }
+    }
 }
 
-pub async fn delete_assignment_file(
+pub async fn regenerate_join_code(
     State(pool): State<PgPool>,
     cookies: Cookies,
-    Path((c_id, a_id, f_id)): Path<(i32, i32, i32)>,
-) -> impl IntoResponse {
+    Path(id): Path<i32>,
+) -> Result<Json<JoinCodeResponse>, (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
     let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
@@ -581,55 +783,45 @@ pub async fn delete_assignment_file(
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
-    let file = sqlx::query!(
-        r#"
-        SELECT id, file_path FROM assignment_files 
-        WHERE id = $1 AND assignment_id = $2
-        "#,
-        f_id,
-        a_id
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        id,
+        claims.sub
     )
     .fetch_optional(&pool)
     .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Database error: {}", e),
-        )
-    })?;
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
 
-    if file.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            format!("File with ID {} not found", f_id),
-        ));
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
     }
 
-    sqlx::query!(
-        r#"
-        DELETE FROM assignment_files WHERE id = $1
-        "#,
-        f_id
+    check_not_archived(&pool, id).await?;
+
+    let join_code = unique_join_code(&pool).await?;
+
+    let classroom = sqlx::query!(
+        "UPDATE classrooms SET join_code = $1, updated_at = NOW() WHERE id = $2 RETURNING join_code, join_code_enabled",
+        join_code,
+        id
     )
-    .execute(&pool)
+    .fetch_one(&pool)
     .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to delete file: {}", e),
-        )
-    })?;
-
-    let _ = std::fs::remove_file(file.unwrap().file_path);
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok((StatusCode::OK, "File deleted successfully"))
+    Ok(Json(JoinCodeResponse {
+        join_code: classroom.join_code,
+        enabled: classroom.join_code_enabled,
+    }))
 }
 
-pub async fn cancel_submission(
+pub async fn update_join_code(
     State(pool): State<PgPool>,
     cookies: Cookies,
-    Path((c_id, a_id)): Path<(i32, i32)>,
-) -> impl IntoResponse {
+    Path(id): Path<i32>,
+    Json(request): Json<UpdateJoinCodeRequest>,
+) -> Result<Json<JoinCodeResponse>, (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
     let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
@@ -642,51 +834,2312 @@ pub async fn cancel_submission(
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
-    let submission = sqlx::query!(
-        r#"
-        SELECT id FROM submissions 
-        WHERE assignment_id = $1 AND user_id = $2
-        "#,
-        a_id,
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        id,
         claims.sub
     )
     .fetch_optional(&pool)
     .await
-    .map_err(|e| {
-        (
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, id).await?;
+
+    let classroom = sqlx::query!(
+        "UPDATE classrooms SET join_code_enabled = $1, updated_at = NOW() WHERE id = $2 RETURNING join_code, join_code_enabled",
+        request.enabled,
+        id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(JoinCodeResponse {
+        join_code: classroom.join_code,
+        enabled: classroom.join_code_enabled,
+    }))
+}
+
+pub async fn create_assignment(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(id): Path<i32>,
+    Json(request): Json<CreateAssignmentRequest>,
+) -> Result<Json<CreatedAssignmentResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, id).await?;
+
+    let parsed_due_date = parse_datetime_input(&request.due_date)?;
+    let late_policy = parse_late_policy(
+        parsed_due_date,
+        request.accept_late,
+        request.late_penalty_per_day,
+        &request.late_cutoff,
+    )?;
+
+    check_category(&pool, id, request.category_id).await?;
+
+    let group_ids = match &request.group_ids {
+        Some(group_ids) => validate_assignment_groups(&pool, id, group_ids).await?,
+        None => Vec::new(),
+    };
+
+    let unique_string_id = uuid::Uuid::new_v4().to_string();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let created_assignment = sqlx::query!(
+        "INSERT INTO assignments (id_base64, classroom_id, title, description, due_date, points, created_by, position, accept_late, late_penalty_per_day, late_cutoff, category_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, (SELECT COALESCE(MAX(position), 0) + 1 FROM assignments WHERE classroom_id = $2), $8, $9, $10, $11)
+        RETURNING id",
+        unique_string_id,
+        id,
+        request.title,
+        request.description,
+        parsed_due_date,
+        request.points,
+        claims.sub,
+        late_policy.accept_late,
+        late_policy.penalty_per_day,
+        late_policy.cutoff,
+        request.category_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    save_assignment_groups(&mut tx, created_assignment.id, &group_ids).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(CreatedAssignmentResponse {
+        id: created_assignment.id,
+    }))
+}
+
+pub async fn get_assignment(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+) -> Result<Json<AssignmentResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let role = classroom_role(&pool, c_id, claims.sub).await?;
+
+    if role == ClassroomRole::Student {
+        check_assignment_visible(&pool, a_id, claims.sub).await?;
+    }
+
+    let assignment = sqlx::query!(
+        r#"
+    SELECT 
+        assignments.id,
+        assignments.classroom_id,
+        assignments.title,
+        assignments.description,
+        assignments.due_date,
+        assignments.points,
+        assignments.category_id,
+        assignments.accept_late,
+        assignments.late_penalty_per_day,
+        assignments.late_cutoff,
+        ext.due_date AS "extended_due_date?",
+        ext.late_cutoff AS "extended_cutoff?",
+        classrooms.name AS class_title
+    FROM assignments
+    JOIN classrooms ON assignments.classroom_id = classrooms.id
+    LEFT JOIN assignment_extensions ext ON ext.assignment_id = assignments.id AND ext.user_id = $3
+    WHERE assignments.classroom_id = $1 AND assignments.id = $2
+    "#,
+        c_id,
+        a_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let assignment = match assignment {
+        Some(assignment) => assignment,
+        None => return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string())),
+    };
+
+    let late_policy = LatePolicy {
+        due_date: assignment.due_date,
+        accept_late: assignment.accept_late,
+        penalty_per_day: assignment.late_penalty_per_day,
+        cutoff: assignment.late_cutoff,
+    }
+    .with_extension(assignment.extended_due_date, assignment.extended_cutoff);
+
+    let materials = sqlx::query!(
+        "SELECT id, file_name, content_type, assignment_file_type FROM assignment_files WHERE assignment_id = $1 AND assignment_file_type = 'material'",
+        a_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let materials: Vec<AssignmentFile> = materials
+        .into_iter()
+        .map(|m| AssignmentFile {
+            id: m.id,
+            file_name: m.file_name,
+            content_type: m.content_type.unwrap_or("unknown".to_string()),
+            file_type: m.assignment_file_type.unwrap_or("unknown".to_string()),
+        })
+        .collect();
+
+    let submission_files = sqlx::query!(
+        "SELECT id, file_name, content_type, assignment_file_type FROM assignment_files WHERE assignment_id = $1 AND user_id = $2 AND assignment_file_type = 'submission'",
+        a_id,
+        claims.sub
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let submission_files: Vec<AssignmentFile> = submission_files
+        .into_iter()
+        .map(|m| AssignmentFile {
+            id: m.id,
+            file_name: m.file_name,
+            content_type: m.content_type.unwrap_or("unknown".to_string()),
+            file_type: m.assignment_file_type.unwrap_or("unknown".to_string()),
+        })
+        .collect();
+
+    let submission = sqlx::query!(
+        r#"SELECT id, grade, feedback, status AS "status: SubmissionStatus", submitted_at FROM submissions WHERE assignment_id = $1 AND user_id = $2"#,
+        a_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let (submission_id, status, submitted_at, grade, feedback) = match submission {
+        Some(s) => (Some(s.id), s.status, s.submitted_at, s.grade, s.feedback),
+        None => (None, SubmissionStatus::Assigned, None, None, None),
+    };
+
+    let grading_scale = load_grading_scale(&pool, c_id).await?;
+
+    let rubric = load_rubric(&pool, a_id).await?;
+    let (rubric_scores, unread_comments) = match submission_id {
+        Some(submission_id) => (
+            load_rubric_scores(&pool, submission_id).await?,
+            unread_comment_count(&pool, submission_id, claims.sub).await?,
+        ),
+        None => (Vec::new(), 0),
+    };
+
+    let group_ids = load_assignment_groups(&pool, a_id).await?;
+
+    Ok(Json(AssignmentResponse {
+        id: assignment.id,
+        class_id: assignment.classroom_id,
+        title: assignment.title,
+        class_title: assignment.class_title,
+        description: assignment
+            .description
+            .unwrap_or_else(|| "No description".to_string()),
+        due_date: late_policy
+            .due_date
+            .map_or_else(|| "No due date".to_string(), |date| date.to_string()),
+        points: assignment.points.unwrap_or(0),
+        category_id: assignment.category_id,
+        accept_late: late_policy.accept_late,
+        late_penalty_per_day: late_policy.penalty_per_day,
+        late_cutoff: late_policy.cutoff.map(|dt| dt.to_string()),
+        has_extension: assignment.extended_due_date.is_some() || assignment.extended_cutoff.is_some(),
+        materials,
+        submission_files,
+        submission_id,
+        unread_comments,
+        is_submitted: submitted_at.is_some(),
+        grade,
+        display_grade: grading_scale.display_grade(grade, assignment.points.unwrap_or(0)),
+        feedback,
+        status,
+        is_late: status.is_late(submitted_at, late_policy.due_date),
+        is_missing: status.is_missing(late_policy.due_date),
+        rubric,
+        rubric_scores,
+        group_ids,
+    }))
+}
+
+pub async fn update_assignment(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+    Json(request): Json<UpdateAssignmentRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let parsed_due_date = parse_datetime_input(&request.due_date)?;
+    let late_policy = parse_late_policy(
+        parsed_due_date,
+        request.accept_late,
+        request.late_penalty_per_day,
+        &request.late_cutoff,
+    )?;
+
+    check_category(&pool, c_id, request.category_id).await?;
+
+    let group_ids = match &request.group_ids {
+        Some(group_ids) => Some(validate_assignment_groups(&pool, c_id, group_ids).await?),
+        None => None,
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE assignments
+        SET title = $1, description = $2, due_date = $3, points = $4,
+            accept_late = $5, late_penalty_per_day = $6, late_cutoff = $7, category_id = $8, updated_at = NOW()
+        WHERE id = $9 AND classroom_id = $10
+        "#,
+        request.title,
+        request.description,
+        parsed_due_date,
+        request.points,
+        late_policy.accept_late,
+        late_policy.penalty_per_day,
+        late_policy.cutoff,
+        request.category_id,
+        a_id,
+        c_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if updated.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string()));
+    }
+
+    // leaving group_ids out keeps the current targets, an empty list reopens it to the whole class
+    if let Some(group_ids) = group_ids {
+        save_assignment_groups(&mut tx, a_id, &group_ids).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Assignment updated successfully".to_string())
+}
+
+pub async fn delete_assignment(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let assignment_exists = sqlx::query!(
+        "SELECT 1 as one FROM assignments WHERE id = $1 AND classroom_id = $2",
+        a_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !assignment_exists {
+        return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string()));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let files = sqlx::query!(
+        "DELETE FROM assignment_files WHERE assignment_id = $1 RETURNING file_path",
+        a_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // submissions are removed by cascade
+    sqlx::query!("DELETE FROM assignments WHERE id = $1", a_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for file in files {
+        let _ = std::fs::remove_file(file.file_path);
+    }
+
+    Ok("Assignment deleted successfully".to_string())
+}
+
+pub async fn reorder_assignments(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Json(request): Json<ReorderAssignmentsRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let mut existing_ids: Vec<i32> = sqlx::query!(
+        "SELECT id FROM assignments WHERE classroom_id = $1",
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .into_iter()
+    .map(|a| a.id)
+    .collect();
+
+    let mut requested_ids = request.assignment_ids.clone();
+    existing_ids.sort();
+    requested_ids.sort();
+
+    if existing_ids != requested_ids {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Order must list every assignment of the classroom exactly once".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE assignments a
+        SET position = o.ord::INTEGER
+        FROM UNNEST($1::INTEGER[]) WITH ORDINALITY AS o(id, ord)
+        WHERE a.id = o.id AND a.classroom_id = $2
+        "#,
+        &request.assignment_ids,
+        c_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Assignments reordered successfully".to_string())
+}
+
+pub async fn list_extensions(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+) -> Result<Json<Vec<ExtensionInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    let extensions = sqlx::query!(
+        r#"
+        SELECT ext.user_id, u.name AS student_name, ext.due_date, ext.late_cutoff, ext.granted_at
+        FROM assignment_extensions ext
+        JOIN assignments a ON ext.assignment_id = a.id
+        JOIN users u ON ext.user_id = u.id
+        WHERE a.id = $1 AND a.classroom_id = $2
+        ORDER BY u.name
+        "#,
+        a_id,
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let extensions: Vec<ExtensionInfo> = extensions
+        .into_iter()
+        .map(|e| ExtensionInfo {
+            user_id: e.user_id,
+            student_name: e.student_name,
+            due_date: e.due_date.map(|dt| dt.to_string()),
+            late_cutoff: e.late_cutoff.map(|dt| dt.to_string()),
+            granted_at: e.granted_at.to_string(),
+        })
+        .collect();
+
+    Ok(Json(extensions))
+}
+
+pub async fn grant_extension(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id, u_id)): Path<(i32, i32, i32)>,
+    Json(request): Json<GrantExtensionRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let assignment_exists = sqlx::query!(
+        "SELECT 1 as one FROM assignments WHERE id = $1 AND classroom_id = $2",
+        a_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !assignment_exists {
+        return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string()));
+    }
+
+    let is_student = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'student'",
+        c_id,
+        u_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_student {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Student is not enrolled in this classroom".to_string(),
+        ));
+    }
+
+    let due_date = parse_datetime_input(&request.due_date)?;
+    let late_cutoff = parse_datetime_input(&request.late_cutoff)?;
+
+    if due_date.is_none() && late_cutoff.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Extension needs a due date or a late cutoff".to_string(),
+        ));
+    }
+
+    if matches!((due_date, late_cutoff), (Some(due_date), Some(cutoff)) if cutoff < due_date) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Late cutoff cannot be before the due date".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO assignment_extensions (assignment_id, user_id, due_date, late_cutoff, granted_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (assignment_id, user_id)
+        DO UPDATE SET due_date = $3, late_cutoff = $4, granted_by = $5, granted_at = NOW()
+        "#,
+        a_id,
+        u_id,
+        due_date,
+        late_cutoff,
+        claims.sub
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Extension granted successfully".to_string())
+}
+
+pub async fn revoke_extension(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id, u_id)): Path<(i32, i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let revoked = sqlx::query!(
+        r#"
+        DELETE FROM assignment_extensions ext
+        USING assignments a
+        WHERE ext.assignment_id = a.id AND a.id = $1 AND a.classroom_id = $2 AND ext.user_id = $3
+        "#,
+        a_id,
+        c_id,
+        u_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if revoked.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Extension not found".to_string()));
+    }
+
+    Ok("Extension revoked successfully".to_string())
+}
+
+async fn load_categories(pool: &PgPool, classroom_id: i32) -> Result<Vec<GradingCategory>, (StatusCode, String)> {
+    sqlx::query_as!(
+        GradingCategory,
+        "SELECT id, classroom_id, name, weight, drop_lowest, created_at, updated_at FROM grading_categories WHERE classroom_id = $1 ORDER BY id",
+        classroom_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn check_category(
+    pool: &PgPool,
+    classroom_id: i32,
+    category_id: Option<i32>,
+) -> Result<(), (StatusCode, String)> {
+    let Some(category_id) = category_id else {
+        return Ok(());
+    };
+
+    let exists = sqlx::query!(
+        "SELECT 1 as one FROM grading_categories WHERE id = $1 AND classroom_id = $2",
+        category_id,
+        classroom_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !exists {
+        return Err((StatusCode::BAD_REQUEST, "Grading category not found".to_string()));
+    }
+
+    Ok(())
+}
+
+fn validate_category_request(request: &GradingCategoryRequest) -> Result<(), (StatusCode, String)> {
+    if request.name.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Category name is required".to_string()));
+    }
+    if !(request.weight > 0.0 && request.weight <= 100.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Category weight must be between 0 and 100 percent".to_string(),
+        ));
+    }
+    if request.drop_lowest.is_some_and(|n| n < 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Number of dropped grades cannot be negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn category_infos(categories: &[GradingCategory]) -> Vec<GradingCategoryInfo> {
+    categories
+        .iter()
+        .map(|c| GradingCategoryInfo {
+            id: c.id,
+            name: c.name.clone(),
+            weight: c.weight,
+            drop_lowest: c.drop_lowest,
+        })
+        .collect()
+}
+
+fn category_grades(categories: &[GradingCategory], scores: &[ScoredAssignment]) -> Vec<CategoryGrade> {
+    categories
+        .iter()
+        .map(|c| CategoryGrade {
+            category_id: c.id,
+            name: c.name.clone(),
+            weight: c.weight,
+            percentage: c.percentage(scores),
+        })
+        .collect()
+}
+
+pub async fn list_categories(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Json<Vec<GradingCategoryInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let is_enrolled = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_enrolled {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "User is not enrolled in this classroom".to_string(),
+        ));
+    }
+
+    let categories = load_categories(&pool, c_id).await?;
+
+    Ok(Json(category_infos(&categories)))
+}
+
+pub async fn create_category(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Json(request): Json<GradingCategoryRequest>,
+) -> Result<Json<GradingCategoryInfo>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    validate_category_request(&request)?;
+
+    let category = sqlx::query!(
+        "INSERT INTO grading_categories (classroom_id, name, weight, drop_lowest) VALUES ($1, $2, $3, $4) RETURNING id, name, weight, drop_lowest",
+        c_id,
+        request.name.trim(),
+        request.weight,
+        request.drop_lowest.unwrap_or(0)
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(GradingCategoryInfo {
+        id: category.id,
+        name: category.name,
+        weight: category.weight,
+        drop_lowest: category.drop_lowest,
+    }))
+}
+
+pub async fn update_category(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, cat_id)): Path<(i32, i32)>,
+    Json(request): Json<GradingCategoryRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    validate_category_request(&request)?;
+
+    let updated = sqlx::query!(
+        "UPDATE grading_categories SET name = $1, weight = $2, drop_lowest = $3, updated_at = NOW() WHERE id = $4 AND classroom_id = $5",
+        request.name.trim(),
+        request.weight,
+        request.drop_lowest.unwrap_or(0),
+        cat_id,
+        c_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if updated.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Grading category not found".to_string()));
+    }
+
+    Ok("Grading category updated successfully".to_string())
+}
+
+pub async fn delete_category(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, cat_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    // assignments of the category become uncategorized (ON DELETE SET NULL)
+    let deleted = sqlx::query!(
+        "DELETE FROM grading_categories WHERE id = $1 AND classroom_id = $2",
+        cat_id,
+        c_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if deleted.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Grading category not found".to_string()));
+    }
+
+    Ok("Grading category deleted successfully".to_string())
+}
+
+async fn load_grading_scale(pool: &PgPool, classroom_id: i32) -> Result<GradingScale, (StatusCode, String)> {
+    let bands = sqlx::query_as!(
+        GradeBand,
+        "SELECT label, min_percentage FROM grading_scale_bands WHERE classroom_id = $1",
+        classroom_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(GradingScale::new(bands))
+}
+
+pub(crate) async fn insert_grading_scale<'e>(
+    executor: impl PgExecutor<'e>,
+    classroom_id: i32,
+    scale: &GradingScale,
+) -> Result<(), (StatusCode, String)> {
+    let labels: Vec<String> = scale.bands.iter().map(|b| b.label.clone()).collect();
+    let mins: Vec<f64> = scale.bands.iter().map(|b| b.min_percentage).collect();
+
+    sqlx::query!(
+        "INSERT INTO grading_scale_bands (classroom_id, label, min_percentage)
+        SELECT $1, * FROM UNNEST($2::VARCHAR[], $3::FLOAT8[])",
+        classroom_id,
+        &labels,
+        &mins
+    )
+    .execute(executor)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(())
+}
+
+fn parse_grading_scale(bands: Vec<GradeBand>) -> Result<GradingScale, (StatusCode, String)> {
+    if bands.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Grading scale needs at least one band".to_string()));
+    }
+
+    let mut parsed = Vec::with_capacity(bands.len());
+    for band in bands {
+        let label = band.label.trim().to_string();
+        if label.is_empty() || label.chars().count() > 32 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Band labels must be between 1 and 32 characters".to_string(),
+            ));
+        }
+        if !(0.0..=100.0).contains(&band.min_percentage) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Band thresholds must be between 0 and 100 percent".to_string(),
+            ));
+        }
+        if parsed.iter().any(|b: &GradeBand| b.min_percentage == band.min_percentage) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Duplicate band threshold {}", band.min_percentage),
+            ));
+        }
+        parsed.push(GradeBand {
+            label,
+            min_percentage: band.min_percentage,
+        });
+    }
+
+    // every score has to land in some band
+    if !parsed.iter().any(|b| b.min_percentage == 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Grading scale needs a band starting at 0 percent".to_string(),
+        ));
+    }
+
+    Ok(GradingScale::new(parsed))
+}
+
+pub async fn get_grading_scale(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Json<GradingScaleResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let is_enrolled = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_enrolled {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "User is not enrolled in this classroom".to_string(),
+        ));
+    }
+
+    let scale = load_grading_scale(&pool, c_id).await?;
+
+    Ok(Json(GradingScaleResponse { bands: scale.bands }))
+}
+
+pub async fn update_grading_scale(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Json(request): Json<GradingScaleRequest>,
+) -> Result<Json<GradingScaleResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let scale = parse_grading_scale(request.bands)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    sqlx::query!("DELETE FROM grading_scale_bands WHERE classroom_id = $1", c_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    insert_grading_scale(&mut *tx, c_id, &scale).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(GradingScaleResponse { bands: scale.bands }))
+}
+
+async fn load_rubric(pool: &PgPool, assignment_id: i32) -> Result<Vec<RubricCriterionInfo>, (StatusCode, String)> {
+    let criteria = sqlx::query!(
+        "SELECT id, title, description FROM rubric_criteria WHERE assignment_id = $1 ORDER BY position, id",
+        assignment_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let levels = sqlx::query!(
+        r#"
+        SELECT l.id, l.criterion_id, l.title, l.description, l.points
+        FROM rubric_levels l
+        JOIN rubric_criteria c ON l.criterion_id = c.id
+        WHERE c.assignment_id = $1
+        ORDER BY l.position, l.id
+        "#,
+        assignment_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut levels_by_criterion: HashMap<i32, Vec<RubricLevelInfo>> = HashMap::new();
+    for level in levels {
+        levels_by_criterion
+            .entry(level.criterion_id)
+            .or_default()
+            .push(RubricLevelInfo {
+                id: level.id,
+                title: level.title,
+                description: level.description,
+                points: level.points,
+            });
+    }
+
+    Ok(criteria
+        .into_iter()
+        .map(|c| {
+            let levels = levels_by_criterion.remove(&c.id).unwrap_or_default();
+            RubricCriterionInfo {
+                id: c.id,
+                title: c.title,
+                description: c.description,
+                max_points: levels.iter().map(|l| l.points).max().unwrap_or(0),
+                levels,
+            }
+        })
+        .collect())
+}
+
+async fn load_rubric_scores(pool: &PgPool, submission_id: i32) -> Result<Vec<RubricScoreInfo>, (StatusCode, String)> {
+    sqlx::query_as!(
+        RubricScoreInfo,
+        r#"
+        SELECT rs.criterion_id, rs.level_id, rs.points, rs.comment
+        FROM submission_rubric_scores rs
+        JOIN rubric_criteria c ON rs.criterion_id = c.id
+        WHERE rs.submission_id = $1
+        ORDER BY c.position, c.id
+        "#,
+        submission_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+fn score_rubric(
+    rubric: &[RubricCriterionInfo],
+    selections: Vec<RubricSelection>,
+) -> Result<Vec<RubricScoreInfo>, (StatusCode, String)> {
+    if rubric.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Assignment has no rubric".to_string()));
+    }
+
+    let mut scores: Vec<RubricScoreInfo> = Vec::with_capacity(selections.len());
+    for selection in selections {
+        let criterion = rubric
+            .iter()
+            .find(|c| c.id == selection.criterion_id)
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                format!("Unknown rubric criterion {}", selection.criterion_id),
+            ))?;
+        let level = criterion
+            .levels
+            .iter()
+            .find(|l| l.id == selection.level_id)
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                format!("Level {} does not belong to criterion '{}'", selection.level_id, criterion.title),
+            ))?;
+        if scores.iter().any(|s| s.criterion_id == criterion.id) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Criterion '{}' was scored more than once", criterion.title),
+            ));
+        }
+        scores.push(RubricScoreInfo {
+            criterion_id: criterion.id,
+            level_id: level.id,
+            points: level.points,
+            comment: selection.comment.filter(|c| !c.trim().is_empty()),
+        });
+    }
+
+    if let Some(missing) = rubric.iter().find(|c| !scores.iter().any(|s| s.criterion_id == c.id)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Criterion '{}' has no level selected", missing.title),
+        ));
+    }
+
+    Ok(scores)
+}
+
+// replaces the rubric scores of a student's submission; an empty slice just clears them
+async fn save_rubric_scores(
+    conn: &mut PgConnection,
+    assignment_id: i32,
+    user_id: i32,
+    scores: &[RubricScoreInfo],
+) -> Result<(), (StatusCode, String)> {
+    sqlx::query!(
+        "DELETE FROM submission_rubric_scores WHERE submission_id = (SELECT id FROM submissions WHERE assignment_id = $1 AND user_id = $2)",
+        assignment_id,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if scores.is_empty() {
+        return Ok(());
+    }
+
+    let criterion_ids: Vec<i32> = scores.iter().map(|s| s.criterion_id).collect();
+    let level_ids: Vec<i32> = scores.iter().map(|s| s.level_id).collect();
+    let points: Vec<i32> = scores.iter().map(|s| s.points).collect();
+    let comments: Vec<Option<String>> = scores.iter().map(|s| s.comment.clone()).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO submission_rubric_scores (submission_id, criterion_id, level_id, points, comment)
+        SELECT s.id, r.criterion_id, r.level_id, r.points, r.comment
+        FROM submissions s
+        CROSS JOIN UNNEST($3::INT[], $4::INT[], $5::INT[], $6::TEXT[]) AS r(criterion_id, level_id, points, comment)
+        WHERE s.assignment_id = $1 AND s.user_id = $2
+        "#,
+        assignment_id,
+        user_id,
+        &criterion_ids,
+        &level_ids,
+        &points,
+        &comments as &[Option<String>]
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(())
+}
+
+pub async fn update_rubric(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+    Json(request): Json<RubricRequest>,
+) -> Result<Json<Vec<RubricCriterionInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let assignment = sqlx::query!(
+        "SELECT id, points FROM assignments WHERE id = $1 AND classroom_id = $2",
+        a_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let assignment = match assignment {
+        Some(assignment) => assignment,
+        None => return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string())),
+    };
+
+    if request.criteria.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Rubric needs at least one criterion".to_string()));
+    }
+    for criterion in &request.criteria {
+        if criterion.title.trim().is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "Criterion title is required".to_string()));
+        }
+        if criterion.levels.is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Criterion '{}' needs at least one level", criterion.title),
+            ));
+        }
+        if criterion.levels.iter().any(|l| l.title.trim().is_empty() || l.points < 0) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Levels of criterion '{}' need a title and non-negative points", criterion.title),
+            ));
+        }
+    }
+
+    // the rubric score becomes the grade, so the best possible score can't go over the assignment points
+    let max_score: i64 = request
+        .criteria
+        .iter()
+        .filter_map(|c| c.levels.iter().map(|l| i64::from(l.points)).max())
+        .sum();
+    let points = assignment.points.unwrap_or(0);
+    if max_score > i64::from(points) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Rubric is worth up to {} points but the assignment only has {}", max_score, points),
+        ));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // scores point at the old criteria, so a rubric already used for grading has to be deleted explicitly
+    let is_used = sqlx::query!(
+        r#"
+        SELECT 1 as one FROM submission_rubric_scores rs
+        JOIN rubric_criteria c ON rs.criterion_id = c.id
+        WHERE c.assignment_id = $1
+        LIMIT 1
+        "#,
+        a_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if is_used {
+        return Err((
+            StatusCode::CONFLICT,
+            "Rubric has already been used for grading".to_string(),
+        ));
+    }
+
+    sqlx::query!("DELETE FROM rubric_criteria WHERE assignment_id = $1", a_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for (position, criterion) in request.criteria.iter().enumerate() {
+        let created = sqlx::query!(
+            "INSERT INTO rubric_criteria (assignment_id, title, description, position) VALUES ($1, $2, $3, $4) RETURNING id",
+            a_id,
+            criterion.title.trim(),
+            criterion.description,
+            position as i32
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        for (level_position, level) in criterion.levels.iter().enumerate() {
+            sqlx::query!(
+                "INSERT INTO rubric_levels (criterion_id, title, description, points, position) VALUES ($1, $2, $3, $4, $5)",
+                created.id,
+                level.title.trim(),
+                level.description,
+                level.points,
+                level_position as i32
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(load_rubric(&pool, a_id).await?))
+}
+
+pub async fn delete_rubric(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((StatusCode::FORBIDDEN, "User is not a teacher".to_string()));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let assignment = sqlx::query!(
+        "SELECT id FROM assignments WHERE id = $1 AND classroom_id = $2",
+        a_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if assignment.is_none() {
+        return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string()));
+    }
+
+    // grades already given stay, only the per-criterion breakdown goes away
+    sqlx::query!("DELETE FROM rubric_criteria WHERE assignment_id = $1", a_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Rubric deleted successfully".to_string())
+}
+
+pub async fn save_submission_multipart_files(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+    mut multipart: Multipart,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let role = classroom_role(&pool, c_id, claims.sub).await?;
+
+    check_not_archived(&pool, c_id).await?;
+
+    if role == ClassroomRole::Student {
+        check_assignment_visible(&pool, a_id, claims.sub).await?;
+    }
+
+    let late_policy = match effective_late_policy(&pool, c_id, a_id, claims.sub).await? {
+        Some(late_policy) => late_policy,
+        None => return Err((StatusCode::NOT_FOUND, "Assignment not found".to_string())),
+    };
+
+    late_policy
+        .check_submission(Utc::now())
+        .map_err(|e| (StatusCode::FORBIDDEN, e.to_string()))?;
+
+    while let Some(field) = multipart.next_field().await.unwrap() {
+        let _name = field.name().unwrap().to_string();
+        let filename = field.file_name().unwrap_or("unknown").to_string();
+        let content_type = field
+            .content_type()
+            .map_or("unknown".to_string(), |ct| ct.to_string());
+        let data = field.bytes().await.unwrap();
+
+        let unique_name = format!("{}_{}", Uuid::new_v4(), filename);
+        let path = format!("./uploads/{}", unique_name);
+
+        let mut file =
+            File::create(&path).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        file.write_all(&data)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        sqlx::query!(
+            "INSERT INTO assignment_files (assignment_id, user_id, file_name, file_path, content_type, assignment_file_type)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            a_id,
+            claims.sub,
+            filename,
+            path,
+            content_type,
+            "submission"
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&path);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+
+        println!("Length of `{}` is {} bytes", filename, data.len());
+    }
+
+    let existing_status = sqlx::query!(
+        r#"SELECT status AS "status: SubmissionStatus" FROM submissions WHERE assignment_id = $1 AND user_id = $2"#,
+        a_id,
+        claims.sub,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map(|s| s.status);
+
+    match existing_status {
+        None => {
+            sqlx::query!(
+                "INSERT INTO submissions (assignment_id, user_id, is_graded, submitted_at, status)
+                VALUES ($1, $2, $3, NOW(), $4)",
+                a_id,
+                claims.sub,
+                false,
+                SubmissionStatus::Assigned.turn_in().as_str()
+            )
+            .execute(&pool)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        }
+        Some(status) => {
+            sqlx::query!(
+                "UPDATE submissions SET submitted_at = NOW(), status = $1 WHERE assignment_id = $2 AND user_id = $3",
+                status.turn_in().as_str(),
+                a_id,
+                claims.sub,
+            )
+            .execute(&pool)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        }
+    }
+
+    Ok("Submission saved successfully".to_string())
+}
+
+pub async fn delete_assignment_file(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id, f_id)): Path<(i32, i32, i32)>,
+) -> impl IntoResponse {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let file = sqlx::query!(
+        r#"
+        SELECT af.id, af.file_path, af.user_id FROM assignment_files af
+        JOIN assignments a ON af.assignment_id = a.id
+        WHERE af.id = $1 AND af.assignment_id = $2 AND a.classroom_id = $3
+        "#,
+        f_id,
+        a_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )
+    })?;
+
+    let file = match file {
+        Some(file) => file,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("File with ID {} not found", f_id),
+            ))
+        }
+    };
+
+    // students can only remove their own uploads, teachers any file of the class
+    if file.user_id != claims.sub && !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "You don't have permission to delete this file".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM assignment_files WHERE id = $1
+        "#,
+        f_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to delete file: {}", e),
+        )
+    })?;
+
+    let _ = std::fs::remove_file(file.file_path);
+
+    Ok((StatusCode::OK, "File deleted successfully"))
+}
+
+pub async fn cancel_submission(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, a_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let submission = sqlx::query!(
+        r#"
+        SELECT s.id, s.status AS "status: SubmissionStatus" FROM submissions s
+        JOIN assignments a ON s.assignment_id = a.id
+        WHERE s.assignment_id = $1 AND s.user_id = $2 AND a.classroom_id = $3
+        "#,
+        a_id,
+        claims.sub,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Database error: {}", e),
         )
     })?;
 
-    if submission.is_none() {
-        return Err((StatusCode::NOT_FOUND, "Submission not found".to_string()));
+    let submission = match submission {
+        Some(submission) => submission,
+        None => return Err((StatusCode::NOT_FOUND, "Submission not found".to_string())),
+    };
+
+    check_not_archived(&pool, c_id).await?;
+
+    let next_status = submission
+        .status
+        .unsubmit()
+        .map_err(|e| (StatusCode::CONFLICT, e.to_string()))?;
+
+    sqlx::query!(
+        r#"
+        UPDATE submissions SET status = $1, submitted_at = NULL WHERE id = $2
+        "#,
+        next_status.as_str(),
+        submission.id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to cancel submission: {}", e),
+        )
+    })?;
+
+    Ok((StatusCode::OK, "Submission was canceled"))
+}
+
+pub async fn list_submissions(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Query(query): Query<SubmissionListQuery>,
+) -> Result<Json<Vec<SubmissionInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can view all submissions".to_string(),
+        ));
+    }
+
+    if let Some(group_id) = query.group_id {
+        check_group(&pool, c_id, group_id).await?;
+    }
+
+    // one row per targeted student and assignment, so unsubmitted work shows up as assigned or missing
+    let submissions = sqlx::query!(
+        r#"
+        SELECT 
+            s.id AS "id?",
+            a.id AS assignment_id,
+            uc.user_id,
+            s.submitted_at AS "submitted_at?",
+            s.is_graded AS "is_graded?",
+            s.grade AS "grade?",
+            s.status AS "status?: SubmissionStatus",
+            u.name as student_name,
+            a.title as assignment_title,
+            COALESCE(ext.due_date, a.due_date) AS due_date,
+            (SELECT COUNT(*) FROM submission_comments sc WHERE sc.submission_id = s.id) AS "comment_count!",
+            (
+                SELECT COUNT(*) FROM submission_comments sc
+                LEFT JOIN submission_comment_reads r ON r.submission_id = sc.submission_id AND r.user_id = $2
+                WHERE sc.submission_id = s.id AND sc.user_id <> $2
+                AND (r.last_read_at IS NULL OR sc.created_at > r.last_read_at)
+            ) AS "unread_comments!"
+        FROM user_classroom_roles uc
+        JOIN users u ON uc.user_id = u.id
+        JOIN assignments a ON a.classroom_id = uc.classroom_id AND assignment_visible_to(a.id, uc.user_id)
+        LEFT JOIN submissions s ON s.assignment_id = a.id AND s.user_id = uc.user_id
+        LEFT JOIN assignment_extensions ext ON ext.assignment_id = a.id AND ext.user_id = uc.user_id
+        WHERE uc.classroom_id = $1 AND uc.role = 'student'
+        AND ($3::INT IS NULL OR EXISTS (
+            SELECT 1 FROM class_group_members gm WHERE gm.group_id = $3 AND gm.user_id = uc.user_id
+        ))
+        ORDER BY s.submitted_at DESC NULLS LAST, a.position, a.id, u.name
+        "#,
+        c_id,
+        claims.sub,
+        query.group_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let submissions: Vec<SubmissionInfo> = submissions
+        .into_iter()
+        .map(|s| {
+            let status = s.status.unwrap_or(SubmissionStatus::Assigned);
+            SubmissionInfo {
+                id: s.id,
+                assignment_id: s.assignment_id,
+                assignment_title: s.assignment_title,
+                user_id: s.user_id,
+                student_name: s.student_name,
+                submitted_at: s.submitted_at.map(|dt| dt.to_string()),
+                is_graded: s.is_graded.unwrap_or(false),
+                grade: s.grade,
+                status,
+                is_late: status.is_late(s.submitted_at, s.due_date),
+                is_missing: status.is_missing(s.due_date),
+                comment_count: s.comment_count,
+                unread_comments: s.unread_comments,
+            }
+        })
+        .filter(|s| match query.status {
+            None => true,
+            Some(SubmissionStatusFilter::Assigned) => s.status == SubmissionStatus::Assigned,
+            Some(SubmissionStatusFilter::TurnedIn) => s.status == SubmissionStatus::TurnedIn,
+            Some(SubmissionStatusFilter::Returned) => s.status == SubmissionStatus::Returned,
+            Some(SubmissionStatusFilter::Resubmitted) => s.status == SubmissionStatus::Resubmitted,
+            Some(SubmissionStatusFilter::Late) => s.is_late,
+            Some(SubmissionStatusFilter::Missing) => s.is_missing,
+        })
+        .collect();
+
+    Ok(Json(submissions))
+}
+
+pub async fn get_gradebook(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Query(query): Query<GradebookQuery>,
+) -> Result<Json<GradebookResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can view the gradebook".to_string(),
+        ));
+    }
+
+    if let Some(group_id) = query.group_id {
+        check_group(&pool, c_id, group_id).await?;
+    }
+
+    let gradebook = load_gradebook(&pool, c_id, query.group_id).await?;
+
+    Ok(Json(gradebook))
+}
+
+async fn load_gradebook(pool: &PgPool, c_id: i32, group_id: Option<i32>) -> Result<GradebookResponse, (StatusCode, String)> {
+    let assignments = sqlx::query!(
+        "SELECT id, title, due_date, points, category_id FROM assignments WHERE classroom_id = $1 ORDER BY position, id",
+        c_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let students = sqlx::query!(
+        r#"
+        SELECT
+            u.id,
+            u.name,
+            u.email,
+            COALESCE(SUM(s.grade), 0) AS "earned_points!",
+            COALESCE(SUM(a.points) FILTER (WHERE s.grade IS NOT NULL), 0) AS "graded_points!",
+            COALESCE(SUM(a.points), 0) AS "total_points!",
+            (100.0 * SUM(s.grade) / NULLIF(SUM(a.points) FILTER (WHERE s.grade IS NOT NULL), 0))::FLOAT8 AS percentage
+        FROM user_classroom_roles uc
+        JOIN users u ON uc.user_id = u.id
+        LEFT JOIN assignments a ON a.classroom_id = uc.classroom_id AND assignment_visible_to(a.id, u.id)
+        LEFT JOIN submissions s ON s.assignment_id = a.id AND s.user_id = u.id
+        WHERE uc.classroom_id = $1 AND uc.role = 'student'
+        AND ($2::INT IS NULL OR EXISTS (
+            SELECT 1 FROM class_group_members gm WHERE gm.group_id = $2 AND gm.user_id = u.id
+        ))
+        GROUP BY u.id, u.name, u.email
+        ORDER BY u.name, u.id
+        "#,
+        c_id,
+        group_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let cells = sqlx::query!(
+        r#"
+        SELECT
+            uc.user_id,
+            a.id AS assignment_id,
+            a.points,
+            a.category_id,
+            s.id AS "submission_id?",
+            s.grade AS "grade?",
+            s.status AS "status?: SubmissionStatus",
+            s.submitted_at AS "submitted_at?",
+            COALESCE(ext.due_date, a.due_date) AS due_date,
+            assignment_visible_to(a.id, uc.user_id) AS "is_assigned!"
+        FROM user_classroom_roles uc
+        JOIN assignments a ON a.classroom_id = uc.classroom_id
+        LEFT JOIN submissions s ON s.assignment_id = a.id AND s.user_id = uc.user_id
+        LEFT JOIN assignment_extensions ext ON ext.assignment_id = a.id AND ext.user_id = uc.user_id
+        WHERE uc.classroom_id = $1 AND uc.role = 'student'
+        ORDER BY uc.user_id, a.position, a.id
+        "#,
+        c_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let categories = load_categories(pool, c_id).await?;
+    let grading_scale = load_grading_scale(pool, c_id).await?;
+
+    let mut grades_by_student: HashMap<i32, Vec<GradebookEntry>> = HashMap::new();
+    let mut scores_by_student: HashMap<i32, Vec<ScoredAssignment>> = HashMap::new();
+    for cell in cells {
+        // every column stays in the row so the export lines up, untargeted cells just don't count
+        if let Some(grade) = cell.grade.filter(|_| cell.is_assigned) {
+            scores_by_student
+                .entry(cell.user_id)
+                .or_default()
+                .push(ScoredAssignment {
+                    category_id: cell.category_id,
+                    grade,
+                    points: cell.points.unwrap_or(0),
+                });
+        }
+
+        let status = cell.status.unwrap_or(SubmissionStatus::Assigned);
+        grades_by_student
+            .entry(cell.user_id)
+            .or_default()
+            .push(GradebookEntry {
+                assignment_id: cell.assignment_id,
+                submission_id: cell.submission_id,
+                grade: cell.grade,
+                display_grade: grading_scale.display_grade(cell.grade, cell.points.unwrap_or(0)),
+                points: cell.points.unwrap_or(0),
+                status,
+                is_late: status.is_late(cell.submitted_at, cell.due_date),
+                is_missing: cell.is_assigned && status.is_missing(cell.due_date),
+                is_assigned: cell.is_assigned,
+            });
+    }
+
+    let assignments: Vec<GradebookAssignment> = assignments
+        .into_iter()
+        .map(|a| GradebookAssignment {
+            id: a.id,
+            title: a.title,
+            due_date: a.due_date.map(|dt| dt.to_string()),
+            points: a.points.unwrap_or(0),
+            category_id: a.category_id,
+        })
+        .collect();
+
+    let students: Vec<GradebookStudent> = students
+        .into_iter()
+        .map(|s| {
+            let scores = scores_by_student.remove(&s.id).unwrap_or_default();
+            let weighted_percentage = weighted_percentage(&categories, &scores);
+            GradebookStudent {
+                user_id: s.id,
+                grades: grades_by_student.remove(&s.id).unwrap_or_default(),
+                name: s.name,
+                email: s.email,
+                earned_points: s.earned_points,
+                graded_points: s.graded_points,
+                total_points: s.total_points,
+                percentage: s.percentage,
+                display_grade: weighted_percentage
+                    .or(s.percentage)
+                    .and_then(|p| grading_scale.label_for(p)),
+                weighted_percentage,
+                category_grades: category_grades(&categories, &scores),
+            }
+        })
+        .collect();
+
+    Ok(GradebookResponse {
+        grading_scale: grading_scale.bands,
+        categories: category_infos(&categories),
+        assignments,
+        students,
+    })
+}
+
+pub async fn export_gradebook(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Query(query): Query<GradebookExportQuery>,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let is_teacher = sqlx::query!(
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
+        c_id,
+        claims.sub
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if !is_teacher {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can export the gradebook".to_string(),
+        ));
+    }
+
+    if let Some(group_id) = query.group_id {
+        check_group(&pool, c_id, group_id).await?;
+    }
+
+    let gradebook = load_gradebook(&pool, c_id, query.group_id).await?;
+
+    let (contents, content_type, extension) = match query.format {
+        GradebookExportFormat::Csv => (gradebook_to_csv(&gradebook)?, "text/csv; charset=utf-8", "csv"),
+        GradebookExportFormat::Xlsx => (
+            gradebook_to_xlsx(&gradebook)?,
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "xlsx",
+        ),
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"gradebook_{}.{}\"", c_id, extension)
+            .parse()
+            .unwrap(),
+    );
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .body(Body::from(contents))
+        .unwrap();
+
+    let (mut parts, body) = response.into_parts();
+    parts.headers = headers;
+
+    Ok(Response::from_parts(parts, body))
+}
+
+fn gradebook_header(gradebook: &GradebookResponse) -> (Vec<String>, Vec<String>) {
+    let mut titles = vec!["Student".to_string(), "Email".to_string()];
+    let mut points = vec!["Points possible".to_string(), String::new()];
+
+    for assignment in &gradebook.assignments {
+        titles.push(assignment.title.clone());
+        points.push(assignment.points.to_string());
+    }
+
+    titles.extend([
+        "Earned".to_string(),
+        "Graded possible".to_string(),
+        "Total possible".to_string(),
+        "Percentage".to_string(),
+        "Weighted".to_string(),
+        "Grade".to_string(),
+    ]);
+    points.extend(std::iter::repeat_n(String::new(), 6));
+
+    (titles, points)
+}
+
+// spreadsheet apps evaluate cells starting with these as formulas
+pub(crate) fn csv_cell(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn gradebook_to_csv(gradebook: &GradebookResponse) -> Result<Vec<u8>, (StatusCode, String)> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let (titles, points) = gradebook_header(gradebook);
+    let titles: Vec<String> = titles.iter().map(|t| csv_cell(t)).collect();
+
+    writer
+        .write_record(&titles)
+        .and_then(|_| writer.write_record(&points))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for student in &gradebook.students {
+        let mut record = vec![csv_cell(&student.name), csv_cell(&student.email)];
+        record.extend(
+            student
+                .grades
+                .iter()
+                .map(|g| g.grade.map_or_else(String::new, |grade| grade.to_string())),
+        );
+        record.push(student.earned_points.to_string());
+        record.push(student.graded_points.to_string());
+        record.push(student.total_points.to_string());
+        record.push(student.percentage.map_or_else(String::new, |p| format!("{:.2}", p)));
+        record.push(
+            student
+                .weighted_percentage
+                .map_or_else(String::new, |p| format!("{:.2}", p)),
+        );
+        record.push(csv_cell(student.display_grade.as_deref().unwrap_or_default()));
+
+        writer
+            .write_record(&record)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+fn gradebook_to_xlsx(gradebook: &GradebookResponse) -> Result<Vec<u8>, (StatusCode, String)> {
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let (titles, _) = gradebook_header(gradebook);
+    let xlsx_error = |e: rust_xlsxwriter::XlsxError| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    for (col, title) in titles.iter().enumerate() {
+        worksheet.write_string(0, col as u16, title).map_err(xlsx_error)?;
     }
 
-    sqlx::query!(
-        r#"
-        DELETE FROM submissions WHERE assignment_id = $1 AND user_id = $2
-        "#,
-        a_id,
-        claims.sub
-    )
-    .execute(&pool)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to delete submission: {}", e),
-        )
-    })?;
+    worksheet.write_string(1, 0, "Points possible").map_err(xlsx_error)?;
+    for (col, assignment) in gradebook.assignments.iter().enumerate() {
+        worksheet
+            .write_number(1, (col + 2) as u16, assignment.points)
+            .map_err(xlsx_error)?;
+    }
 
-    Ok((StatusCode::OK, "Submission was canceled"))
+    for (index, student) in gradebook.students.iter().enumerate() {
+        let row = (index + 2) as u32;
+        worksheet.write_string(row, 0, &student.name).map_err(xlsx_error)?;
+        worksheet.write_string(row, 1, &student.email).map_err(xlsx_error)?;
+
+        for (col, entry) in student.grades.iter().enumerate() {
+            if let Some(grade) = entry.grade {
+                worksheet
+                    .write_number(row, (col + 2) as u16, grade)
+                    .map_err(xlsx_error)?;
+            }
+        }
+
+        let totals_col = (gradebook.assignments.len() + 2) as u16;
+        worksheet
+            .write_number(row, totals_col, student.earned_points as f64)
+            .map_err(xlsx_error)?;
+        worksheet
+            .write_number(row, totals_col + 1, student.graded_points as f64)
+            .map_err(xlsx_error)?;
+        worksheet
+            .write_number(row, totals_col + 2, student.total_points as f64)
+            .map_err(xlsx_error)?;
+        if let Some(percentage) = student.percentage {
+            worksheet
+                .write_number(row, totals_col + 3, (percentage * 100.0).round() / 100.0)
+                .map_err(xlsx_error)?;
+        }
+        if let Some(weighted) = student.weighted_percentage {
+            worksheet
+                .write_number(row, totals_col + 4, (weighted * 100.0).round() / 100.0)
+                .map_err(xlsx_error)?;
+        }
+        if let Some(display_grade) = &student.display_grade {
+            worksheet
+                .write_string(row, totals_col + 5, display_grade)
+                .map_err(xlsx_error)?;
+        }
+    }
+
+    workbook.save_to_buffer().map_err(xlsx_error)
 }
 
-pub async fn list_submissions(
+#[derive(Deserialize)]
+struct GradeImportRow {
+    email: String,
+    assignment: String,
+    grade: String,
+    #[serde(default)]
+    feedback: Option<String>,
+}
+
+pub async fn import_grades(
     State(pool): State<PgPool>,
     cookies: Cookies,
     Path(c_id): Path<i32>,
-) -> Result<Json<Vec<SubmissionInfo>>, (StatusCode, String)> {
+    Query(query): Query<GradeImportQuery>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<GradeImportReport>), (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
     let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
@@ -700,7 +3153,7 @@ pub async fn list_submissions(
     };
 
     let is_teacher = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'teacher'",
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
         c_id,
         claims.sub
     )
@@ -712,47 +3165,235 @@ pub async fn list_submissions(
     if !is_teacher {
         return Err((
             StatusCode::FORBIDDEN,
-            "Only teachers can view all submissions".to_string(),
+            "Only teachers can import grades".to_string(),
         ));
     }
 
-    let submissions = sqlx::query!(
+    check_not_archived(&pool, c_id).await?;
+
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::BAD_REQUEST, "CSV file is missing".to_string()))?;
+    let data = field
+        .bytes()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let students: HashMap<String, i32> = sqlx::query!(
         r#"
-        SELECT 
-            s.id,
-            s.assignment_id,
-            s.user_id,
-            s.submitted_at,
-            s.is_graded,
-            s.grade,
-            u.name as student_name,
-            a.title as assignment_title
+        SELECT u.id, u.email FROM users u
+        JOIN user_classroom_roles uc ON uc.user_id = u.id
+        WHERE uc.classroom_id = $1 AND uc.role = 'student'
+        "#,
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .into_iter()
+    .map(|u| (u.email.to_lowercase(), u.id))
+    .collect();
+
+    let assignments = sqlx::query!(
+        "SELECT id, title, points, due_date, accept_late, late_penalty_per_day, late_cutoff FROM assignments WHERE classroom_id = $1",
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let extensions: HashMap<_, _> = sqlx::query!(
+        r#"
+        SELECT ext.assignment_id, ext.user_id, ext.due_date, ext.late_cutoff
+        FROM assignment_extensions ext
+        JOIN assignments a ON ext.assignment_id = a.id
+        WHERE a.classroom_id = $1
+        "#,
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .into_iter()
+    .map(|e| ((e.assignment_id, e.user_id), (e.due_date, e.late_cutoff)))
+    .collect();
+
+    let submitted_at: HashMap<(i32, i32), Option<DateTime<Utc>>> = sqlx::query!(
+        r#"
+        SELECT s.assignment_id, s.user_id, s.submitted_at
         FROM submissions s
-        JOIN users u ON s.user_id = u.id
         JOIN assignments a ON s.assignment_id = a.id
         WHERE a.classroom_id = $1
-        ORDER BY s.submitted_at DESC
         "#,
         c_id
     )
     .fetch_all(&pool)
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .into_iter()
+    .map(|s| ((s.assignment_id, s.user_id), s.submitted_at))
+    .collect();
+
+    let mut report = GradeImportReport {
+        dry_run: query.dry_run,
+        applied: false,
+        total_rows: 0,
+        valid_rows: 0,
+        unknown_students: Vec::new(),
+        unknown_assignments: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut grades: HashMap<(i32, i32), (usize, i32, Option<String>)> = HashMap::new();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(data.as_ref());
+
+    for (index, record) in reader.deserialize::<GradeImportRow>().enumerate() {
+        // header is line 1
+        let row = index + 2;
+        report.total_rows += 1;
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                report.errors.push(GradeImportError { row, message: e.to_string() });
+                continue;
+            }
+        };
+
+        let student_id = students.get(&record.email.to_lowercase()).copied();
+        if student_id.is_none() {
+            if !report.unknown_students.contains(&record.email) {
+                report.unknown_students.push(record.email.clone());
+            }
+            report.errors.push(GradeImportError {
+                row,
+                message: format!("Unknown student `{}`", record.email),
+            });
+        }
 
-    let submissions: Vec<SubmissionInfo> = submissions
-        .into_iter()
-        .map(|s| SubmissionInfo {
-            id: s.id,
-            assignment_id: s.assignment_id,
-            assignment_title: s.assignment_title,
-            student_name: s.student_name,
-            submitted_at: s.submitted_at.map(|dt| dt.to_string()),
-            is_graded: s.is_graded,
-            grade: s.grade,
-        })
-        .collect();
+        let by_id = record
+            .assignment
+            .parse::<i32>()
+            .ok()
+            .and_then(|id| assignments.iter().find(|a| a.id == id));
+        let matching: Vec<_> = match by_id {
+            Some(assignment) => vec![assignment],
+            None => assignments
+                .iter()
+                .filter(|a| a.title.trim().eq_ignore_ascii_case(&record.assignment))
+                .collect(),
+        };
+        let assignment = match matching.as_slice() {
+            [assignment] => Some(*assignment),
+            [] => {
+                if !report.unknown_assignments.contains(&record.assignment) {
+                    report.unknown_assignments.push(record.assignment.clone());
+                }
+                report.errors.push(GradeImportError {
+                    row,
+                    message: format!("Unknown assignment `{}`", record.assignment),
+                });
+                None
+            }
+            _ => {
+                report.errors.push(GradeImportError {
+                    row,
+                    message: format!("Assignment title `{}` is ambiguous, use its id", record.assignment),
+                });
+                None
+            }
+        };
+
+        let grade = match record.grade.parse::<i32>() {
+            Ok(grade) => Some(grade),
+            Err(_) => {
+                report.errors.push(GradeImportError {
+                    row,
+                    message: format!("Invalid grade `{}`", record.grade),
+                });
+                None
+            }
+        };
+
+        let (Some(student_id), Some(assignment), Some(grade)) = (student_id, assignment, grade) else {
+            continue;
+        };
+
+        if let Err(message) = validate_grade(grade, assignment.points) {
+            report.errors.push(GradeImportError {
+                row,
+                message: format!("{} of `{}`", message, assignment.title),
+            });
+            continue;
+        }
 
-    Ok(Json(submissions))
+        let feedback = record.feedback.filter(|f| !f.is_empty());
+        if let Some((first_row, _, _)) = grades.insert((assignment.id, student_id), (row, grade, feedback)) {
+            report.errors.push(GradeImportError {
+                row,
+                message: format!("Duplicate grade, already given on row {}", first_row),
+            });
+            continue;
+        }
+
+        report.valid_rows += 1;
+    }
+
+    if !report.errors.is_empty() {
+        let status = if query.dry_run { StatusCode::OK } else { StatusCode::UNPROCESSABLE_ENTITY };
+        return Ok((status, Json(report)));
+    }
+
+    if query.dry_run {
+        return Ok((StatusCode::OK, Json(report)));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for ((assignment_id, student_id), (_, grade, feedback)) in grades {
+        let assignment = assignments.iter().find(|a| a.id == assignment_id).unwrap();
+        let (extended_due_date, extended_cutoff) = extensions
+            .get(&(assignment_id, student_id))
+            .copied()
+            .unwrap_or((None, None));
+        let late_policy = LatePolicy {
+            due_date: assignment.due_date,
+            accept_late: assignment.accept_late,
+            penalty_per_day: assignment.late_penalty_per_day,
+            cutoff: assignment.late_cutoff,
+        }
+        .with_extension(extended_due_date, extended_cutoff);
+
+        save_grade(
+            &mut *tx,
+            assignment_id,
+            student_id,
+            &late_policy,
+            submitted_at.get(&(assignment_id, student_id)).copied().flatten(),
+            Some(grade),
+            feedback,
+            claims.sub,
+        )
+        .await?;
+
+        save_rubric_scores(&mut tx, assignment_id, student_id, &[]).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    report.applied = true;
+
+    Ok((StatusCode::OK, Json(report)))
 }
 
 pub async fn get_submission(
@@ -773,7 +3414,7 @@ pub async fn get_submission(
     };
 
     let is_teacher = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'teacher'",
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
         c_id,
         claims.sub
     )
@@ -798,11 +3439,14 @@ pub async fn get_submission(
             s.submitted_at,
             s.is_graded,
             s.grade,
+            s.feedback,
+            s.status AS "status: SubmissionStatus",
             s.graded_at,
             s.graded_by,
             u.name as student_name,
             a.title as assignment_title,
             a.points as assignment_points,
+            s.raw_grade,
             COALESCE(grader.name) as grader_name
         FROM submissions s
         JOIN users u ON s.user_id = u.id
@@ -822,6 +3466,10 @@ pub async fn get_submission(
         None => return Err((StatusCode::NOT_FOUND, "Submission not found".to_string())),
     };
 
+    let late_policy = effective_late_policy(&pool, c_id, submission.assignment_id, submission.user_id)
+        .await?
+        .ok_or((StatusCode::NOT_FOUND, "Assignment not found".to_string()))?;
+
     let files = sqlx::query!(
         "SELECT id, file_name, content_type FROM assignment_files WHERE assignment_id = $1 AND user_id = $2 AND assignment_file_type = 'submission'",
         submission.assignment_id,
@@ -841,6 +3489,10 @@ pub async fn get_submission(
         })
         .collect();
 
+    let grading_scale = load_grading_scale(&pool, c_id).await?;
+    let rubric = load_rubric(&pool, submission.assignment_id).await?;
+    let rubric_scores = load_rubric_scores(&pool, submission.id).await?;
+
     Ok(Json(SubmissionResponse {
         id: submission.id,
         assignment_id: submission.assignment_id,
@@ -850,9 +3502,18 @@ pub async fn get_submission(
         submitted_at: submission.submitted_at.map(|dt| dt.to_string()),
         is_graded: submission.is_graded,
         grade: submission.grade,
+        raw_grade: submission.raw_grade,
+        display_grade: grading_scale.display_grade(submission.grade, submission.assignment_points.unwrap_or(0)),
+        feedback: submission.feedback,
+        status: submission.status,
+        is_late: submission.status.is_late(submission.submitted_at, late_policy.due_date),
+        is_missing: submission.status.is_missing(late_policy.due_date),
+        days_late: late_policy.days_late(submission.submitted_at),
         graded_at: submission.graded_at.map(|dt| dt.to_string()),
         grader_name: submission.grader_name,
-        files: files,
+        files,
+        rubric,
+        rubric_scores,
     }))
 }
 
@@ -875,7 +3536,7 @@ pub async fn grade_submission(
     };
 
     let is_teacher = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'teacher'",
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
         c_id,
         claims.sub
     )
@@ -891,9 +3552,12 @@ pub async fn grade_submission(
         ));
     }
 
-    let submission_exists = sqlx::query!(
+    check_not_archived(&pool, c_id).await?;
+
+    let submission = sqlx::query!(
         r#"
-        SELECT 1 as one FROM submissions s
+        SELECT s.assignment_id, s.user_id, s.submitted_at, a.points
+        FROM submissions s
         JOIN assignments a ON s.assignment_id = a.id
         WHERE s.id = $1 AND a.classroom_id = $2
         "#,
@@ -902,28 +3566,104 @@ pub async fn grade_submission(
     )
     .fetch_optional(&pool)
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let submission = match submission {
+        Some(submission) => submission,
+        None => return Err((StatusCode::NOT_FOUND, "Submission not found".to_string())),
+    };
+
+    let late_policy = effective_late_policy(&pool, c_id, submission.assignment_id, submission.user_id)
+        .await?
+        .ok_or((StatusCode::NOT_FOUND, "Assignment not found".to_string()))?;
+
+    // with rubric selections the grade is the sum of the selected levels
+    let (grade, rubric_scores) = match request.rubric {
+        Some(selections) => {
+            let rubric = load_rubric(&pool, submission.assignment_id).await?;
+            let scores = score_rubric(&rubric, selections)?;
+            (Some(scores.iter().map(|s| s.points).sum()), scores)
+        }
+        None => (request.grade, Vec::new()),
+    };
+
+    if let Some(grade) = grade {
+        validate_grade(grade, submission.points).map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    save_grade(
+        &mut *tx,
+        submission.assignment_id,
+        submission.user_id,
+        &late_policy,
+        submission.submitted_at,
+        grade,
+        request.feedback,
+        claims.sub,
+    )
+    .await?;
+
+    save_rubric_scores(&mut tx, submission.assignment_id, submission.user_id, &rubric_scores).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Submission graded successfully".to_string())
+}
 
-    if !submission_exists {
-        return Err((StatusCode::NOT_FOUND, "Submission not found".to_string()));
+// shared by manual grading and the csv import so both accept the same range
+fn validate_grade(grade: i32, points: Option<i32>) -> Result<(), String> {
+    let points = points.unwrap_or(0);
+    if grade < 0 {
+        return Err(format!("Grade {} can't be negative", grade));
+    }
+    if grade > points {
+        return Err(format!("Grade {} exceeds the {} points", grade, points));
     }
+    Ok(())
+}
 
+#[allow(clippy::too_many_arguments)]
+async fn save_grade<'e>(
+    executor: impl PgExecutor<'e>,
+    assignment_id: i32,
+    user_id: i32,
+    late_policy: &LatePolicy,
+    submitted_at: Option<DateTime<Utc>>,
+    grade: Option<i32>,
+    feedback: Option<String>,
+    grader_id: i32,
+) -> Result<(), (StatusCode, String)> {
+    let penalized_grade = grade.map(|grade| late_policy.apply_penalty(grade, submitted_at));
+
+    // work graded without anything handed in (e.g. offline) gets a row with no submitted_at,
+    // and grading without feedback keeps whatever was written before
     sqlx::query!(
         r#"
-        UPDATE submissions 
-        SET grade = $1, is_graded = true, graded_at = NOW(), graded_by = $2
-        WHERE id = $3
+        INSERT INTO submissions (assignment_id, user_id, submitted_at, is_graded, grade, raw_grade, feedback, graded_at, graded_by, status)
+        VALUES ($1, $2, NULL, true, $3, $4, $5, NOW(), $6, $7)
+        ON CONFLICT (assignment_id, user_id)
+        DO UPDATE SET grade = $3, raw_grade = $4, feedback = COALESCE($5, submissions.feedback), is_graded = true, graded_at = NOW(), graded_by = $6, status = $7
         "#,
-        request.grade,
-        claims.sub,
-        s_id
+        assignment_id,
+        user_id,
+        penalized_grade,
+        grade,
+        feedback,
+        grader_id,
+        SubmissionStatus::Returned.as_str()
     )
-    .execute(&pool)
+    .execute(executor)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok("Submission graded successfully".to_string())
+    Ok(())
 }
 
 pub async fn cancel_grade(
@@ -944,7 +3684,7 @@ pub async fn cancel_grade(
     };
 
     let is_teacher = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'teacher'",
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
         c_id,
         claims.sub
     )
@@ -960,9 +3700,11 @@ pub async fn cancel_grade(
         ));
     }
 
-    let submission_exists = sqlx::query!(
+    check_not_archived(&pool, c_id).await?;
+
+    let submission = sqlx::query!(
         r#"
-        SELECT 1 as one FROM submissions s
+        SELECT s.status AS "status: SubmissionStatus", s.submitted_at FROM submissions s
         JOIN assignments a ON s.assignment_id = a.id
         WHERE s.id = $1 AND a.classroom_id = $2
         "#,
@@ -971,28 +3713,200 @@ pub async fn cancel_grade(
     )
     .fetch_optional(&pool)
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    if !submission_exists {
-        return Err((StatusCode::NOT_FOUND, "Submission not found".to_string()));
-    }
+    let submission = match submission {
+        Some(submission) => submission,
+        None => return Err((StatusCode::NOT_FOUND, "Submission not found".to_string())),
+    };
+
+    let next_status = submission
+        .status
+        .cancel_grade(submission.submitted_at.is_some())
+        .map_err(|e| (StatusCode::CONFLICT, e.to_string()))?;
 
     sqlx::query!(
         r#"
         UPDATE submissions 
-        SET grade = null, is_graded = false, graded_by = null, graded_at = null, submitted_at = null
-        WHERE id = $1
+        SET grade = null, raw_grade = null, feedback = null, is_graded = false, graded_by = null, graded_at = null, status = $1
+        WHERE id = $2
         "#,
+        next_status.as_str(),
         s_id
     )
     .execute(&pool)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    sqlx::query!("DELETE FROM submission_rubric_scores WHERE submission_id = $1", s_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
     Ok("Grade canceled successfully".to_string())
 }
 
+async fn unread_comment_count(pool: &PgPool, submission_id: i32, user_id: i32) -> Result<i64, (StatusCode, String)> {
+    let unread = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM submission_comments sc
+        LEFT JOIN submission_comment_reads r ON r.submission_id = sc.submission_id AND r.user_id = $2
+        WHERE sc.submission_id = $1 AND sc.user_id <> $2
+        AND (r.last_read_at IS NULL OR sc.created_at > r.last_read_at)
+        "#,
+        submission_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(unread.count)
+}
+
+// only the student who owns the submission and the classroom's teachers take part in its thread
+async fn check_submission_participant(
+    pool: &PgPool,
+    c_id: i32,
+    s_id: i32,
+    user_id: i32,
+) -> Result<(), (StatusCode, String)> {
+    let submission = sqlx::query!(
+        r#"
+        SELECT s.user_id, uc.role AS "role?: ClassroomRole"
+        FROM submissions s
+        JOIN assignments a ON s.assignment_id = a.id
+        LEFT JOIN user_classroom_roles uc ON uc.classroom_id = a.classroom_id AND uc.user_id = $3
+        WHERE s.id = $1 AND a.classroom_id = $2
+        "#,
+        s_id,
+        c_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let submission = match submission {
+        Some(submission) => submission,
+        None => return Err((StatusCode::NOT_FOUND, "Submission not found".to_string())),
+    };
+
+    let is_owner = submission.user_id == user_id && submission.role.is_some();
+    let is_teacher = submission.role.as_ref().is_some_and(|role| role.is_teacher());
+
+    if !is_owner && !is_teacher {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "You don't have access to this submission".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn list_submission_comments(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, s_id)): Path<(i32, i32)>,
+) -> Result<Json<Vec<CommentInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    check_submission_participant(&pool, c_id, s_id, claims.sub).await?;
+
+    let comments = sqlx::query!(
+        r#"
+        SELECT sc.id, sc.user_id, u.name AS author_name, sc.body, sc.created_at
+        FROM submission_comments sc
+        JOIN users u ON sc.user_id = u.id
+        WHERE sc.submission_id = $1
+        ORDER BY sc.created_at, sc.id
+        "#,
+        s_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    sqlx::query!(
+        "INSERT INTO submission_comment_reads (submission_id, user_id, last_read_at) VALUES ($1, $2, NOW())
+        ON CONFLICT (submission_id, user_id) DO UPDATE SET last_read_at = NOW()",
+        s_id,
+        claims.sub
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        comments
+            .into_iter()
+            .map(|c| CommentInfo {
+                id: c.id,
+                author_id: c.user_id,
+                author_name: c.author_name,
+                body: c.body,
+                created_at: c.created_at.to_string(),
+            })
+            .collect(),
+    ))
+}
+
+pub async fn create_submission_comment(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, s_id)): Path<(i32, i32)>,
+    Json(request): Json<CommentRequest>,
+) -> Result<Json<CommentInfo>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    check_submission_participant(&pool, c_id, s_id, claims.sub).await?;
+    check_not_archived(&pool, c_id).await?;
+
+    if request.body.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Comment cannot be empty".to_string()));
+    }
+
+    let comment = sqlx::query!(
+        r#"
+        WITH inserted AS (
+            INSERT INTO submission_comments (submission_id, user_id, body)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, body, created_at
+        )
+        SELECT i.id, i.user_id, u.name AS author_name, i.body, i.created_at
+        FROM inserted i
+        JOIN users u ON i.user_id = u.id
+        "#,
+        s_id,
+        claims.sub,
+        request.body
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // the author has obviously seen the thread up to their own comment
+    sqlx::query!(
+        "INSERT INTO submission_comment_reads (submission_id, user_id, last_read_at) VALUES ($1, $2, $3)
+        ON CONFLICT (submission_id, user_id) DO UPDATE SET last_read_at = GREATEST(submission_comment_reads.last_read_at, $3)",
+        s_id,
+        claims.sub,
+        comment.created_at
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(CommentInfo {
+        id: comment.id,
+        author_id: comment.user_id,
+        author_name: comment.author_name,
+        body: comment.body,
+        created_at: comment.created_at.to_string(),
+    }))
+}
+
 pub async fn download_submission_file(
     State(pool): State<PgPool>,
     cookies: Cookies,
@@ -1036,7 +3950,7 @@ pub async fn download_submission_file(
     };
 
     let is_teacher = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role = 'teacher'",
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
         c_id,
         claims.sub
     )
@@ -1118,6 +4032,7 @@ pub async fn download_material_file(
             af.file_path,
             af.content_type,
             af.user_id,
+            af.assignment_id,
             a.classroom_id
         FROM assignment_files af
         JOIN assignments a ON af.assignment_id = a.id
@@ -1135,21 +4050,10 @@ pub async fn download_material_file(
         None => return Err((StatusCode::NOT_FOUND, "File not found".to_string())),
     };
 
-    let is_enrolled = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
-        c_id,
-        claims.sub
-    )
-    .fetch_optional(&pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .is_some();
+    let role = classroom_role(&pool, c_id, claims.sub).await?;
 
-    if !is_enrolled {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "You don't have permission to download this file".to_string(),
-        ));
+    if role == ClassroomRole::Student {
+        check_assignment_visible(&pool, file.assignment_id, claims.sub).await?;
     }
 
     let mut file_handle = File::open(&file.file_path).map_err(|e| {
@@ -1212,7 +4116,7 @@ pub async fn add_assignment_materials(
     };
 
     let is_teacher = sqlx::query!(
-        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role='teacher'",
+        "SELECT 1 as one FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role IN ('creator', 'teacher')",
         c_id,
         claims.sub
     )
@@ -1228,7 +4132,9 @@ pub async fn add_assignment_materials(
         ));
     }
 
-    while let Some(mut field) = multipart.next_field().await.unwrap() {
+    check_not_archived(&pool, c_id).await?;
+
+    while let Some(field) = multipart.next_field().await.unwrap() {
         let _name = field.name().unwrap().to_string();
         let filename = field.file_name().unwrap_or("unknown").to_string();
         let content_type = field
@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use axum::extract::Path;
+use axum::{Json, extract::State, http::StatusCode};
+use sqlx::{PgConnection, PgPool};
+use tower_cookies::Cookies;
+
+use crate::dto::{GroupInfo, GroupMember, GroupMembersRequest, GroupRequest};
+use crate::handlers::classroom::{check_not_archived, classroom_role};
+use crate::middlewares::jwt::check_auth;
+
+pub(crate) async fn check_group(pool: &PgPool, classroom_id: i32, group_id: i32) -> Result<(), (StatusCode, String)> {
+    let group = sqlx::query!(
+        "SELECT 1 as one FROM class_groups WHERE id = $1 AND classroom_id = $2",
+        group_id,
+        classroom_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    match group {
+        Some(_) => Ok(()),
+        None => Err((StatusCode::NOT_FOUND, "Group not found".to_string())),
+    }
+}
+
+// students outside every targeted group don't see the assignment at all
+pub(crate) async fn check_assignment_visible(
+    pool: &PgPool,
+    assignment_id: i32,
+    user_id: i32,
+) -> Result<(), (StatusCode, String)> {
+    let visible = sqlx::query!(
+        r#"
+        SELECT 1 as one FROM assignments a
+        WHERE a.id = $1 AND assignment_visible_to(a.id, $2)
+        "#,
+        assignment_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    match visible {
+        Some(_) => Ok(()),
+        None => Err((StatusCode::NOT_FOUND, "Assignment not found".to_string())),
+    }
+}
+
+pub(crate) async fn load_assignment_groups(pool: &PgPool, assignment_id: i32) -> Result<Vec<i32>, (StatusCode, String)> {
+    let groups = sqlx::query!(
+        "SELECT group_id FROM assignment_groups WHERE assignment_id = $1 ORDER BY group_id",
+        assignment_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(groups.into_iter().map(|g| g.group_id).collect())
+}
+
+pub(crate) async fn validate_assignment_groups(
+    pool: &PgPool,
+    classroom_id: i32,
+    group_ids: &[i32],
+) -> Result<Vec<i32>, (StatusCode, String)> {
+    let mut group_ids = group_ids.to_vec();
+    group_ids.sort_unstable();
+    group_ids.dedup();
+
+    let found = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM class_groups WHERE classroom_id = $1 AND id = ANY($2)"#,
+        classroom_id,
+        &group_ids
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if found.count != group_ids.len() as i64 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Assignments can only target groups of their own classroom".to_string(),
+        ));
+    }
+
+    Ok(group_ids)
+}
+
+pub(crate) async fn save_assignment_groups(
+    conn: &mut PgConnection,
+    assignment_id: i32,
+    group_ids: &[i32],
+) -> Result<(), (StatusCode, String)> {
+    sqlx::query!("DELETE FROM assignment_groups WHERE assignment_id = $1", assignment_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    sqlx::query!(
+        "INSERT INTO assignment_groups (assignment_id, group_id) SELECT $1, UNNEST($2::INT[])",
+        assignment_id,
+        group_ids
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(())
+}
+
+pub(crate) async fn leave_groups(
+    conn: &mut PgConnection,
+    classroom_id: i32,
+    user_id: i32,
+) -> Result<(), (StatusCode, String)> {
+    sqlx::query!(
+        r#"
+        DELETE FROM class_group_members gm
+        USING class_groups g
+        WHERE gm.group_id = g.id AND g.classroom_id = $1 AND gm.user_id = $2
+        "#,
+        classroom_id,
+        user_id
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(())
+}
+
+async fn load_groups(pool: &PgPool, classroom_id: i32) -> Result<Vec<GroupInfo>, (StatusCode, String)> {
+    let groups = sqlx::query!(
+        r#"
+        SELECT g.id, g.name,
+            (SELECT COUNT(*) FROM assignment_groups ag WHERE ag.group_id = g.id) AS "assignment_count!"
+        FROM class_groups g
+        WHERE g.classroom_id = $1
+        ORDER BY g.name, g.id
+        "#,
+        classroom_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let members = sqlx::query!(
+        r#"
+        SELECT gm.group_id, u.id, u.name, u.email
+        FROM class_group_members gm
+        JOIN class_groups g ON gm.group_id = g.id
+        JOIN users u ON gm.user_id = u.id
+        WHERE g.classroom_id = $1
+        ORDER BY u.name, u.id
+        "#,
+        classroom_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut members_by_group: HashMap<i32, Vec<GroupMember>> = HashMap::new();
+    for member in members {
+        members_by_group.entry(member.group_id).or_default().push(GroupMember {
+            user_id: member.id,
+            name: member.name,
+            email: member.email,
+        });
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|g| GroupInfo {
+            members: members_by_group.remove(&g.id).unwrap_or_default(),
+            id: g.id,
+            name: g.name,
+            assignment_count: g.assignment_count,
+        })
+        .collect())
+}
+
+async fn check_group_name(
+    pool: &PgPool,
+    classroom_id: i32,
+    group_id: Option<i32>,
+    name: &str,
+) -> Result<(), (StatusCode, String)> {
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Group name must not be empty".to_string()));
+    }
+
+    let taken = sqlx::query!(
+        "SELECT 1 as one FROM class_groups WHERE classroom_id = $1 AND LOWER(name) = LOWER($2) AND id IS DISTINCT FROM $3",
+        classroom_id,
+        name,
+        group_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if taken {
+        return Err((
+            StatusCode::CONFLICT,
+            "A group with this name already exists".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn list_groups(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Json<Vec<GroupInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can view groups".to_string(),
+        ));
+    }
+
+    Ok(Json(load_groups(&pool, c_id).await?))
+}
+
+pub async fn create_group(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Json(request): Json<GroupRequest>,
+) -> Result<Json<GroupInfo>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can manage groups".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let name = request.name.trim();
+    check_group_name(&pool, c_id, None, name).await?;
+
+    let group = sqlx::query!(
+        "INSERT INTO class_groups (classroom_id, name) VALUES ($1, $2) RETURNING id, name",
+        c_id,
+        name
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(GroupInfo {
+        id: group.id,
+        name: group.name,
+        members: Vec::new(),
+        assignment_count: 0,
+    }))
+}
+
+pub async fn update_group(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, g_id)): Path<(i32, i32)>,
+    Json(request): Json<GroupRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can manage groups".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+    check_group(&pool, c_id, g_id).await?;
+
+    let name = request.name.trim();
+    check_group_name(&pool, c_id, Some(g_id), name).await?;
+
+    sqlx::query!(
+        "UPDATE class_groups SET name = $1, updated_at = NOW() WHERE id = $2",
+        name,
+        g_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Group updated successfully".to_string())
+}
+
+pub async fn delete_group(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, g_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can manage groups".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+    check_group(&pool, c_id, g_id).await?;
+
+    // dropping the last target would silently hand the assignment to the whole class
+    let is_targeted = sqlx::query!(
+        "SELECT 1 as one FROM assignment_groups WHERE group_id = $1",
+        g_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if is_targeted {
+        return Err((
+            StatusCode::CONFLICT,
+            "Group is targeted by assignments, retarget them first".to_string(),
+        ));
+    }
+
+    sqlx::query!("DELETE FROM class_groups WHERE id = $1", g_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Group deleted successfully".to_string())
+}
+
+pub async fn add_group_members(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, g_id)): Path<(i32, i32)>,
+    Json(request): Json<GroupMembersRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can manage groups".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+    check_group(&pool, c_id, g_id).await?;
+
+    let students = sqlx::query!(
+        "SELECT user_id FROM user_classroom_roles WHERE classroom_id = $1 AND role = 'student' AND user_id = ANY($2)",
+        c_id,
+        &request.user_ids
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if let Some(user_id) = request
+        .user_ids
+        .iter()
+        .find(|id| !students.iter().any(|s| s.user_id == **id))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("User {} is not a student in this classroom", user_id),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO class_group_members (group_id, user_id)
+        SELECT $1, UNNEST($2::INT[])
+        ON CONFLICT (group_id, user_id) DO NOTHING
+        "#,
+        g_id,
+        &request.user_ids
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Group members added successfully".to_string())
+}
+
+pub async fn remove_group_member(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, g_id, u_id)): Path<(i32, i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can manage groups".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+    check_group(&pool, c_id, g_id).await?;
+
+    let removed = sqlx::query!(
+        "DELETE FROM class_group_members WHERE group_id = $1 AND user_id = $2",
+        g_id,
+        u_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if removed.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "User is not in this group".to_string()));
+    }
+
+    Ok("Group member removed successfully".to_string())
+}
@@ -0,0 +1,1022 @@
+use axum::extract::{Path, Query};
+use axum::{Json, extract::State, http::StatusCode};
+use chrono::Utc;
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+use crate::dto::{CreateInviteLinkRequest, EnrollmentRequestInfo, InvitationInfo, InviteLinkInfo, InviteTeacherRequest, MemberRole, MembershipQuery, UpdateMemberRoleRequest};
+use crate::handlers::auth::refresh_auth_cookie;
+use crate::handlers::classroom::{check_not_archived, classroom_role, parse_datetime_input};
+use crate::handlers::groups::leave_groups;
+use crate::middlewares::jwt::check_auth;
+use crate::models::{ClassroomRole, EnrollmentStatus, InvitationStatus, InviteLink};
+
+// tokens issued before this carry stale classroom roles and get reissued on the next session check
+pub(crate) async fn touch_roles<'e>(executor: impl PgExecutor<'e>, user_id: i32) -> Result<(), (StatusCode, String)> {
+    sqlx::query!("UPDATE users SET roles_updated_at = NOW() WHERE id = $1", user_id)
+        .execute(executor)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(())
+}
+
+pub async fn invite_teacher(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Json(request): Json<InviteTeacherRequest>,
+) -> Result<Json<InvitationInfo>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can invite co-teachers".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let email = request.email.trim().to_lowercase();
+    if !email.contains('@') {
+        return Err((StatusCode::BAD_REQUEST, "Invalid email address".to_string()));
+    }
+
+    let is_teacher = sqlx::query!(
+        r#"
+        SELECT 1 as one FROM user_classroom_roles uc
+        JOIN users u ON uc.user_id = u.id
+        WHERE uc.classroom_id = $1 AND LOWER(u.email) = $2 AND uc.role IN ('creator', 'teacher')
+        "#,
+        c_id,
+        email
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if is_teacher {
+        return Err((
+            StatusCode::CONFLICT,
+            "User is already a teacher in this classroom".to_string(),
+        ));
+    }
+
+    let is_invited = sqlx::query!(
+        "SELECT 1 as one FROM classroom_invitations WHERE classroom_id = $1 AND LOWER(email) = $2 AND status = $3",
+        c_id,
+        email,
+        InvitationStatus::Pending.as_str()
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .is_some();
+
+    if is_invited {
+        return Err((
+            StatusCode::CONFLICT,
+            "This email already has a pending invitation".to_string(),
+        ));
+    }
+
+    let invitation = sqlx::query!(
+        r#"
+        WITH inserted AS (
+            INSERT INTO classroom_invitations (classroom_id, email, role, invited_by, status)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, classroom_id, email, invited_by, status, created_at
+        )
+        SELECT i.id, i.classroom_id, c.name AS class_title, i.email, u.name AS invited_by,
+            i.status AS "status: InvitationStatus", i.created_at
+        FROM inserted i
+        JOIN classrooms c ON i.classroom_id = c.id
+        JOIN users u ON i.invited_by = u.id
+        "#,
+        c_id,
+        email,
+        ClassroomRole::Teacher.as_str(),
+        claims.sub,
+        InvitationStatus::Pending.as_str()
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(InvitationInfo {
+        id: invitation.id,
+        class_id: invitation.classroom_id,
+        class_title: invitation.class_title,
+        email: invitation.email,
+        invited_by: invitation.invited_by,
+        status: invitation.status,
+        created_at: invitation.created_at.to_string(),
+    }))
+}
+
+pub async fn list_invitations(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Json<Vec<InvitationInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can view invitations".to_string(),
+        ));
+    }
+
+    let invitations = sqlx::query!(
+        r#"
+        SELECT i.id, i.classroom_id, c.name AS class_title, i.email, u.name AS invited_by,
+            i.status AS "status: InvitationStatus", i.created_at
+        FROM classroom_invitations i
+        JOIN classrooms c ON i.classroom_id = c.id
+        JOIN users u ON i.invited_by = u.id
+        WHERE i.classroom_id = $1 AND i.status = $2
+        ORDER BY i.created_at DESC
+        "#,
+        c_id,
+        InvitationStatus::Pending.as_str()
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        invitations
+            .into_iter()
+            .map(|i| InvitationInfo {
+                id: i.id,
+                class_id: i.classroom_id,
+                class_title: i.class_title,
+                email: i.email,
+                invited_by: i.invited_by,
+                status: i.status,
+                created_at: i.created_at.to_string(),
+            })
+            .collect(),
+    ))
+}
+
+pub async fn revoke_invitation(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, inv_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can revoke invitations".to_string(),
+        ));
+    }
+
+    let revoked = sqlx::query!(
+        "UPDATE classroom_invitations SET status = $1, responded_at = NOW() WHERE id = $2 AND classroom_id = $3 AND status = $4",
+        InvitationStatus::Revoked.as_str(),
+        inv_id,
+        c_id,
+        InvitationStatus::Pending.as_str()
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if revoked.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Invitation not found".to_string()));
+    }
+
+    Ok("Invitation revoked successfully".to_string())
+}
+
+pub async fn list_my_invitations(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+) -> Result<Json<Vec<InvitationInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let invitations = sqlx::query!(
+        r#"
+        SELECT i.id, i.classroom_id, c.name AS class_title, i.email, inviter.name AS invited_by,
+            i.status AS "status: InvitationStatus", i.created_at
+        FROM classroom_invitations i
+        JOIN users me ON LOWER(me.email) = LOWER(i.email)
+        JOIN classrooms c ON i.classroom_id = c.id
+        JOIN users inviter ON i.invited_by = inviter.id
+        WHERE me.id = $1 AND i.status = $2 AND c.archived_at IS NULL
+        ORDER BY i.created_at DESC
+        "#,
+        claims.sub,
+        InvitationStatus::Pending.as_str()
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        invitations
+            .into_iter()
+            .map(|i| InvitationInfo {
+                id: i.id,
+                class_id: i.classroom_id,
+                class_title: i.class_title,
+                email: i.email,
+                invited_by: i.invited_by,
+                status: i.status,
+                created_at: i.created_at.to_string(),
+            })
+            .collect(),
+    ))
+}
+
+pub async fn accept_invitation(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(inv_id): Path<i32>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies.clone()).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let invitation = sqlx::query!(
+        r#"
+        SELECT i.classroom_id FROM classroom_invitations i
+        JOIN users me ON LOWER(me.email) = LOWER(i.email)
+        WHERE i.id = $1 AND me.id = $2 AND i.status = $3
+        FOR UPDATE OF i
+        "#,
+        inv_id,
+        claims.sub,
+        InvitationStatus::Pending.as_str()
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let invitation = match invitation {
+        Some(invitation) => invitation,
+        None => return Err((StatusCode::NOT_FOUND, "Invitation not found".to_string())),
+    };
+
+    check_not_archived(&pool, invitation.classroom_id).await?;
+
+    // an enrolled student is promoted, an existing teacher or the creator keeps their role
+    sqlx::query!(
+        r#"
+        INSERT INTO user_classroom_roles (user_id, classroom_id, role) VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, classroom_id) DO UPDATE SET role = $3
+        WHERE user_classroom_roles.role = $4
+        "#,
+        claims.sub,
+        invitation.classroom_id,
+        ClassroomRole::Teacher.as_str(),
+        ClassroomRole::Student.as_str()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    sqlx::query!(
+        "UPDATE classroom_invitations SET status = $1, responded_at = NOW() WHERE id = $2",
+        InvitationStatus::Accepted.as_str(),
+        inv_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    touch_roles(&mut *tx, claims.sub).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    refresh_auth_cookie(&pool, &cookies, claims.sub).await?;
+
+    Ok("Invitation accepted".to_string())
+}
+
+pub async fn decline_invitation(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(inv_id): Path<i32>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let declined = sqlx::query!(
+        r#"
+        UPDATE classroom_invitations i SET status = $1, responded_at = NOW()
+        FROM users me
+        WHERE i.id = $2 AND me.id = $3 AND LOWER(me.email) = LOWER(i.email) AND i.status = $4
+        "#,
+        InvitationStatus::Declined.as_str(),
+        inv_id,
+        claims.sub,
+        InvitationStatus::Pending.as_str()
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if declined.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Invitation not found".to_string()));
+    }
+
+    Ok("Invitation declined".to_string())
+}
+
+fn parse_invite_restrictions(
+    allowed_emails: Option<Vec<String>>,
+    allowed_domain: Option<String>,
+) -> Result<(Vec<String>, Option<String>), (StatusCode, String)> {
+    let mut emails = Vec::new();
+    for email in allowed_emails.unwrap_or_default() {
+        let email = email.trim().to_lowercase();
+        if !email.contains('@') {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid email address: {}", email),
+            ));
+        }
+        if !emails.contains(&email) {
+            emails.push(email);
+        }
+    }
+
+    let domain = match allowed_domain {
+        Some(domain) => {
+            let domain = domain.trim().trim_start_matches('@').to_lowercase();
+            if domain.is_empty() || domain.contains('@') || !domain.contains('.') {
+                return Err((StatusCode::BAD_REQUEST, "Invalid email domain".to_string()));
+            }
+            Some(domain)
+        }
+        None => None,
+    };
+
+    Ok((emails, domain))
+}
+
+pub(crate) struct FoundInviteLink {
+    pub id: i32,
+    pub classroom_id: i32,
+    pub link: InviteLink,
+}
+
+pub(crate) async fn find_invite_link(pool: &PgPool, token: &str) -> Result<Option<FoundInviteLink>, (StatusCode, String)> {
+    let link = sqlx::query!(
+        r#"
+        SELECT id, classroom_id, expires_at, max_uses, use_count, allowed_emails, allowed_domain, revoked_at
+        FROM classroom_invite_links
+        WHERE token = $1
+        "#,
+        token.trim()
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(link.map(|l| FoundInviteLink {
+        id: l.id,
+        classroom_id: l.classroom_id,
+        link: InviteLink {
+            expires_at: l.expires_at,
+            max_uses: l.max_uses,
+            use_count: l.use_count,
+            allowed_emails: l.allowed_emails,
+            allowed_domain: l.allowed_domain,
+            revoked: l.revoked_at.is_some(),
+        },
+    }))
+}
+
+// the guard in WHERE keeps concurrent joins from going past max_uses
+pub(crate) async fn redeem_invite_link(conn: &mut PgConnection, link_id: i32) -> Result<(), (StatusCode, String)> {
+    let redeemed = sqlx::query!(
+        r#"
+        UPDATE classroom_invite_links SET use_count = use_count + 1
+        WHERE id = $1 AND revoked_at IS NULL AND (max_uses IS NULL OR use_count < max_uses)
+        "#,
+        link_id
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if redeemed.rows_affected() == 0 {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "This invite link has been revoked or has reached its maximum number of uses".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+// pending requests hold a use of the link until they are answered, otherwise a single use link
+// could queue any number of requests that all fail on approval
+pub(crate) async fn reserve_invite_link(conn: &mut PgConnection, link_id: i32) -> Result<(), (StatusCode, String)> {
+    let link = sqlx::query!(
+        "SELECT max_uses, use_count FROM classroom_invite_links WHERE id = $1 FOR UPDATE",
+        link_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let Some(max_uses) = link.max_uses else {
+        return Ok(());
+    };
+
+    let pending = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM enrollment_requests WHERE invite_link_id = $1 AND status = $2"#,
+        link_id,
+        EnrollmentStatus::Pending.as_str()
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .count;
+
+    if i64::from(link.use_count) + pending >= i64::from(max_uses) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "This invite link has reached its maximum number of uses".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn create_invite_link(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Json(request): Json<CreateInviteLinkRequest>,
+) -> Result<Json<InviteLinkInfo>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can create invite links".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let expires_at = parse_datetime_input(&request.expires_at)?;
+    if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Expiry time must be in the future".to_string(),
+        ));
+    }
+
+    if request.max_uses.is_some_and(|max_uses| max_uses < 1) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Max uses must be at least 1".to_string(),
+        ));
+    }
+
+    let (allowed_emails, allowed_domain) = parse_invite_restrictions(request.allowed_emails, request.allowed_domain)?;
+    let token = Uuid::new_v4().simple().to_string();
+
+    let link = sqlx::query!(
+        r#"
+        WITH inserted AS (
+            INSERT INTO classroom_invite_links (classroom_id, token, created_by, expires_at, max_uses, allowed_emails, allowed_domain)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, token, created_by, expires_at, max_uses, use_count, allowed_emails, allowed_domain, created_at
+        )
+        SELECT i.id, i.token, u.name AS created_by, i.expires_at, i.max_uses, i.use_count,
+            i.allowed_emails, i.allowed_domain, i.created_at
+        FROM inserted i
+        JOIN users u ON i.created_by = u.id
+        "#,
+        c_id,
+        token,
+        claims.sub,
+        expires_at,
+        request.max_uses,
+        &allowed_emails,
+        allowed_domain
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(InviteLinkInfo {
+        id: link.id,
+        token: link.token,
+        created_by: link.created_by,
+        expires_at: link.expires_at.map(|d| d.to_string()),
+        max_uses: link.max_uses,
+        use_count: link.use_count,
+        allowed_emails: link.allowed_emails,
+        allowed_domain: link.allowed_domain,
+        created_at: link.created_at.to_string(),
+    }))
+}
+
+pub async fn list_invite_links(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Json<Vec<InviteLinkInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can view invite links".to_string(),
+        ));
+    }
+
+    // only links that can still be used
+    let links = sqlx::query!(
+        r#"
+        SELECT l.id, l.token, u.name AS created_by, l.expires_at, l.max_uses, l.use_count,
+            l.allowed_emails, l.allowed_domain, l.created_at
+        FROM classroom_invite_links l
+        JOIN users u ON l.created_by = u.id
+        WHERE l.classroom_id = $1
+            AND l.revoked_at IS NULL
+            AND (l.expires_at IS NULL OR l.expires_at > NOW())
+            AND (l.max_uses IS NULL OR l.use_count < l.max_uses)
+        ORDER BY l.created_at DESC
+        "#,
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        links
+            .into_iter()
+            .map(|l| InviteLinkInfo {
+                id: l.id,
+                token: l.token,
+                created_by: l.created_by,
+                expires_at: l.expires_at.map(|d| d.to_string()),
+                max_uses: l.max_uses,
+                use_count: l.use_count,
+                allowed_emails: l.allowed_emails,
+                allowed_domain: l.allowed_domain,
+                created_at: l.created_at.to_string(),
+            })
+            .collect(),
+    ))
+}
+
+pub async fn revoke_invite_link(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, link_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can revoke invite links".to_string(),
+        ));
+    }
+
+    let revoked = sqlx::query!(
+        "UPDATE classroom_invite_links SET revoked_at = NOW() WHERE id = $1 AND classroom_id = $2 AND revoked_at IS NULL",
+        link_id,
+        c_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if revoked.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Invite link not found".to_string()));
+    }
+
+    Ok("Invite link revoked successfully".to_string())
+}
+
+pub async fn list_enrollment_requests(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Json<Vec<EnrollmentRequestInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can view enrollment requests".to_string(),
+        ));
+    }
+
+    let requests = sqlx::query!(
+        r#"
+        SELECT r.id, r.classroom_id, c.name AS class_title, r.user_id, u.name AS user_name, u.email,
+            r.status AS "status: EnrollmentStatus", r.created_at
+        FROM enrollment_requests r
+        JOIN classrooms c ON r.classroom_id = c.id
+        JOIN users u ON r.user_id = u.id
+        WHERE r.classroom_id = $1 AND r.status = $2
+        ORDER BY r.created_at
+        "#,
+        c_id,
+        EnrollmentStatus::Pending.as_str()
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        requests
+            .into_iter()
+            .map(|r| EnrollmentRequestInfo {
+                id: r.id,
+                class_id: r.classroom_id,
+                class_title: r.class_title,
+                user_id: r.user_id,
+                user_name: r.user_name,
+                email: r.email,
+                status: r.status,
+                created_at: r.created_at.to_string(),
+            })
+            .collect(),
+    ))
+}
+
+pub async fn list_my_enrollment_requests(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+) -> Result<Json<Vec<EnrollmentRequestInfo>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let requests = sqlx::query!(
+        r#"
+        SELECT r.id, r.classroom_id, c.name AS class_title, r.user_id, u.name AS user_name, u.email,
+            r.status AS "status: EnrollmentStatus", r.created_at
+        FROM enrollment_requests r
+        JOIN classrooms c ON r.classroom_id = c.id
+        JOIN users u ON r.user_id = u.id
+        WHERE r.user_id = $1 AND r.status = $2
+        ORDER BY r.created_at DESC
+        "#,
+        claims.sub,
+        EnrollmentStatus::Pending.as_str()
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        requests
+            .into_iter()
+            .map(|r| EnrollmentRequestInfo {
+                id: r.id,
+                class_id: r.classroom_id,
+                class_title: r.class_title,
+                user_id: r.user_id,
+                user_name: r.user_name,
+                email: r.email,
+                status: r.status,
+                created_at: r.created_at.to_string(),
+            })
+            .collect(),
+    ))
+}
+
+pub async fn approve_enrollment_request(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, req_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can approve enrollment requests".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let request = sqlx::query!(
+        r#"
+        UPDATE enrollment_requests SET status = $1, responded_at = NOW(), responded_by = $2
+        WHERE id = $3 AND classroom_id = $4 AND status = $5
+        RETURNING user_id, invite_link_id
+        "#,
+        EnrollmentStatus::Approved.as_str(),
+        claims.sub,
+        req_id,
+        c_id,
+        EnrollmentStatus::Pending.as_str()
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let request = match request {
+        Some(request) => request,
+        None => return Err((StatusCode::NOT_FOUND, "Enrollment request not found".to_string())),
+    };
+
+    // the user may have been added some other way while the request was waiting
+    let enrolled = sqlx::query!(
+        r#"
+        INSERT INTO user_classroom_roles (user_id, classroom_id, role) VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, classroom_id) DO NOTHING
+        "#,
+        request.user_id,
+        c_id,
+        ClassroomRole::Student.as_str()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if let Some(link_id) = request.invite_link_id.filter(|_| enrolled.rows_affected() > 0) {
+        redeem_invite_link(&mut tx, link_id).await?;
+    }
+
+    touch_roles(&mut *tx, request.user_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Enrollment request approved".to_string())
+}
+
+pub async fn reject_enrollment_request(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, req_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can reject enrollment requests".to_string(),
+        ));
+    }
+
+    let rejected = sqlx::query!(
+        r#"
+        UPDATE enrollment_requests SET status = $1, responded_at = NOW(), responded_by = $2
+        WHERE id = $3 AND classroom_id = $4 AND status = $5
+        "#,
+        EnrollmentStatus::Rejected.as_str(),
+        claims.sub,
+        req_id,
+        c_id,
+        EnrollmentStatus::Pending.as_str()
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if rejected.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Enrollment request not found".to_string()));
+    }
+
+    Ok("Enrollment request rejected".to_string())
+}
+
+pub async fn update_member_role(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, u_id)): Path<(i32, i32)>,
+    Json(request): Json<UpdateMemberRoleRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if classroom_role(&pool, c_id, claims.sub).await? != ClassroomRole::Creator {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only the classroom creator can change member roles".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let member = sqlx::query!(
+        r#"SELECT role AS "role: ClassroomRole" FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2"#,
+        c_id,
+        u_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let member = match member {
+        Some(member) => member,
+        None => return Err((StatusCode::NOT_FOUND, "Member not found".to_string())),
+    };
+
+    if member.role == ClassroomRole::Creator {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The creator's role cannot be changed".to_string(),
+        ));
+    }
+
+    let role = match request.role {
+        MemberRole::Teacher => ClassroomRole::Teacher,
+        MemberRole::Student => ClassroomRole::Student,
+    };
+
+    sqlx::query!(
+        "UPDATE user_classroom_roles SET role = $1 WHERE classroom_id = $2 AND user_id = $3",
+        role.as_str(),
+        c_id,
+        u_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    touch_roles(&pool, u_id).await?;
+
+    Ok("Member role updated successfully".to_string())
+}
+
+// removes everything the member handed in to this classroom and returns the files to delete from disk
+async fn purge_member_work(
+    conn: &mut PgConnection,
+    classroom_id: i32,
+    user_id: i32,
+) -> Result<Vec<String>, (StatusCode, String)> {
+    let files = sqlx::query!(
+        r#"
+        DELETE FROM assignment_files
+        WHERE user_id = $2 AND assignment_file_type = 'submission'
+        AND assignment_id IN (SELECT id FROM assignments WHERE classroom_id = $1)
+        RETURNING file_path
+        "#,
+        classroom_id,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // rubric scores and private comments go with the submissions by cascade
+    sqlx::query!(
+        "DELETE FROM submissions WHERE user_id = $2 AND assignment_id IN (SELECT id FROM assignments WHERE classroom_id = $1)",
+        classroom_id,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    sqlx::query!(
+        "DELETE FROM assignment_extensions WHERE user_id = $2 AND assignment_id IN (SELECT id FROM assignments WHERE classroom_id = $1)",
+        classroom_id,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(files.into_iter().map(|f| f.file_path).collect())
+}
+
+async fn unenroll(pool: &PgPool, classroom_id: i32, user_id: i32, purge: bool) -> Result<(), (StatusCode, String)> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let files = if purge {
+        purge_member_work(&mut tx, classroom_id, user_id).await?
+    } else {
+        Vec::new()
+    };
+
+    sqlx::query!(
+        "DELETE FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2",
+        classroom_id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    leave_groups(&mut tx, classroom_id, user_id).await?;
+    touch_roles(&mut *tx, user_id).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for file in files {
+        let _ = std::fs::remove_file(file);
+    }
+
+    Ok(())
+}
+
+pub async fn remove_member(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, u_id)): Path<(i32, i32)>,
+    Query(query): Query<MembershipQuery>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let role = classroom_role(&pool, c_id, claims.sub).await?;
+    if !role.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can remove members".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    if u_id == claims.sub {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Use the leave endpoint to leave a classroom".to_string(),
+        ));
+    }
+
+    let member = sqlx::query!(
+        r#"SELECT role AS "role: ClassroomRole" FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2"#,
+        c_id,
+        u_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let member = match member {
+        Some(member) => member,
+        None => return Err((StatusCode::NOT_FOUND, "Member not found".to_string())),
+    };
+
+    match member.role {
+        ClassroomRole::Creator => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "The classroom creator cannot be removed".to_string(),
+            ));
+        }
+        ClassroomRole::Teacher if role != ClassroomRole::Creator => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Only the classroom creator can remove teachers".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    unenroll(&pool, c_id, u_id, query.purge).await?;
+
+    Ok("Member removed successfully".to_string())
+}
+
+pub async fn leave_class(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies.clone()).await?;
+
+    if classroom_role(&pool, c_id, claims.sub).await? == ClassroomRole::Creator {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The classroom creator cannot leave; archive or delete the classroom instead".to_string(),
+        ));
+    }
+
+    // only a teacher removing the student can throw the work away, leaving always keeps it
+    unenroll(&pool, c_id, claims.sub, false).await?;
+
+    refresh_auth_cookie(&pool, &cookies, claims.sub).await?;
+
+    Ok("Left classroom successfully".to_string())
+}
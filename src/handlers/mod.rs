@@ -1,3 +1,8 @@
 pub(crate) mod user;
 pub(crate) mod auth;
-pub(crate) mod classroom;
\ No newline at end of file
+pub(crate) mod classroom;
+pub(crate) mod stream;
+pub(crate) mod members;
+pub(crate) mod roster;
+pub(crate) mod oneroster;
+pub(crate) mod groups;
\ No newline at end of file
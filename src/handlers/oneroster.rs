@@ -0,0 +1,874 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
+
+use axum::body::Body;
+use axum::extract::{Path, Query};
+use axum::http::{HeaderMap, Response, header};
+use axum::{Json, extract::Multipart, extract::State, http::StatusCode};
+use chrono::SecondsFormat;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use sqlx::{PgConnection, PgPool};
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+use crate::dto::{OneRosterImportError, OneRosterImportQuery, OneRosterImportReport, PasswordSetupLink};
+use crate::handlers::auth::issue_password_setup_token;
+use crate::handlers::classroom::{classroom_role, insert_grading_scale, unique_join_code};
+use crate::handlers::groups::leave_groups;
+use crate::handlers::members::touch_roles;
+use crate::middlewares::jwt::check_auth;
+use crate::models::{ClassroomRole, GradingScale, SubmissionStatus};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrgRow {
+    sourced_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserRow {
+    sourced_id: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    enabled_user: String,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    given_name: String,
+    #[serde(default)]
+    family_name: String,
+    #[serde(default)]
+    email: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClassRow {
+    sourced_id: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    class_code: String,
+    #[serde(default)]
+    school_sourced_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnrollmentRow {
+    #[serde(default)]
+    sourced_id: String,
+    #[serde(default)]
+    status: String,
+    class_sourced_id: String,
+    user_sourced_id: String,
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    primary: String,
+}
+
+// delta exports mark removed records instead of leaving them out
+fn is_deleted(status: &str) -> bool {
+    status.eq_ignore_ascii_case("tobedeleted")
+}
+
+fn import_error(file: &str, row: usize, sourced_id: &str, message: String) -> OneRosterImportError {
+    OneRosterImportError {
+        file: file.to_string(),
+        row,
+        sourced_id: (!sourced_id.is_empty()).then(|| sourced_id.to_string()),
+        message,
+    }
+}
+
+// a compressed entry can claim any size, so the csv files are also read through a capped reader
+const MAX_BUNDLE_SIZE: u64 = 100 * 1024 * 1024;
+
+fn read_bundle(data: &[u8]) -> Result<HashMap<String, Vec<u8>>, (StatusCode, String)> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid OneRoster bundle: {}", e)))?;
+    let mut files = HashMap::new();
+    let mut remaining = MAX_BUNDLE_SIZE;
+    let too_large = || {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("OneRoster bundle expands to more than {} MB", MAX_BUNDLE_SIZE / 1024 / 1024),
+        )
+    };
+
+    for index in 0..archive.len() {
+        let mut file = archive
+            .by_index(index)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid OneRoster bundle: {}", e)))?;
+
+        // some systems zip the csv files together with their enclosing folder
+        let name = file.name().rsplit('/').next().unwrap_or_default().to_lowercase();
+        if !name.ends_with(".csv") {
+            continue;
+        }
+
+        if file.size() > remaining {
+            return Err(too_large());
+        }
+
+        let mut contents = Vec::new();
+        (&mut file)
+            .take(remaining + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        if contents.len() as u64 > remaining {
+            return Err(too_large());
+        }
+        remaining -= contents.len() as u64;
+
+        files.insert(name, contents);
+    }
+
+    Ok(files)
+}
+
+fn read_rows<T: DeserializeOwned>(
+    files: &HashMap<String, Vec<u8>>,
+    name: &str,
+    report: &mut OneRosterImportReport,
+) -> Option<Vec<(usize, T)>> {
+    let data = files.get(name)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(data.as_slice());
+    let mut rows = Vec::new();
+
+    for (index, record) in reader.deserialize::<T>().enumerate() {
+        // header is line 1
+        let row = index + 2;
+        match record {
+            Ok(record) => rows.push((row, record)),
+            Err(e) => report.errors.push(import_error(name, row, "", e.to_string())),
+        }
+    }
+
+    Some(rows)
+}
+
+async fn import_users(
+    conn: &mut PgConnection,
+    rows: Vec<(usize, UserRow)>,
+    report: &mut OneRosterImportReport,
+    created_by: i32,
+) -> Result<(), (StatusCode, String)> {
+    for (row, user) in rows {
+        if user.sourced_id.is_empty() {
+            report.errors.push(import_error("users.csv", row, "", "sourcedId is required".to_string()));
+            continue;
+        }
+        if is_deleted(&user.status) || user.enabled_user.eq_ignore_ascii_case("false") {
+            report.skipped += 1;
+            continue;
+        }
+
+        let email = user.email.to_lowercase();
+        if !email.contains('@') {
+            report.errors.push(import_error(
+                "users.csv",
+                row,
+                &user.sourced_id,
+                "A valid email is required".to_string(),
+            ));
+            continue;
+        }
+
+        let name = format!("{} {}", user.given_name, user.family_name).trim().to_string();
+        let name = if !name.is_empty() {
+            name
+        } else if !user.username.is_empty() {
+            user.username.clone()
+        } else {
+            email.clone()
+        };
+
+        let existing = sqlx::query!("SELECT id FROM users WHERE sourced_id = $1", user.sourced_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        // accounts registered before the first sync are linked by their email
+        let existing = match existing {
+            Some(existing) => Some(existing.id),
+            None => sqlx::query!(
+                "SELECT id FROM users WHERE LOWER(email) = $1 AND sourced_id IS NULL",
+                email
+            )
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .map(|u| u.id),
+        };
+
+        let email_taken = sqlx::query!(
+            "SELECT 1 as one FROM users WHERE LOWER(email) = $1 AND id IS DISTINCT FROM $2",
+            email,
+            existing
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .is_some();
+
+        if email_taken {
+            report.errors.push(import_error(
+                "users.csv",
+                row,
+                &user.sourced_id,
+                format!("Email `{}` belongs to another user", email),
+            ));
+            continue;
+        }
+
+        match existing {
+            Some(user_id) => {
+                // keep the casing the user logs in with when only that differs
+                let updated = sqlx::query!(
+                    r#"
+                    UPDATE users SET name = $1, email = CASE WHEN LOWER(email) = $2 THEN email ELSE $2 END,
+                        sourced_id = $3, updated_at = NOW()
+                    WHERE id = $4
+                    RETURNING password_hash = '' AND NOT EXISTS (
+                        SELECT 1 FROM password_setup_tokens t
+                        WHERE t.user_id = users.id AND t.used_at IS NULL AND t.expires_at > NOW()
+                    ) AS "needs_setup_link!"
+                    "#,
+                    name,
+                    email,
+                    user.sourced_id,
+                    user_id
+                )
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+                // a link is only reissued once the previous one expired, a sync must not invalidate
+                // links that were already handed out
+                if updated.needs_setup_link {
+                    let token = issue_password_setup_token(&mut *conn, user_id, created_by).await?;
+                    report.password_setup.push(PasswordSetupLink {
+                        sourced_id: user.sourced_id,
+                        email,
+                        token,
+                    });
+                }
+
+                report.users_updated += 1;
+            }
+            None => {
+                let created = sqlx::query!(
+                    "INSERT INTO users (name, email, password_hash, sourced_id) VALUES ($1, $2, '', $3) RETURNING id",
+                    name,
+                    email,
+                    user.sourced_id
+                )
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+                let token = issue_password_setup_token(&mut *conn, created.id, created_by).await?;
+                report.password_setup.push(PasswordSetupLink {
+                    sourced_id: user.sourced_id,
+                    email,
+                    token,
+                });
+                report.users_created += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn class_creator(
+    conn: &mut PgConnection,
+    class_sourced_id: &str,
+    enrollments: &[(usize, EnrollmentRow)],
+) -> Result<Option<i32>, (StatusCode, String)> {
+    let mut teachers: Vec<&EnrollmentRow> = enrollments
+        .iter()
+        .map(|(_, e)| e)
+        .filter(|e| e.class_sourced_id == class_sourced_id && e.role.eq_ignore_ascii_case("teacher") && !is_deleted(&e.status))
+        .collect();
+    teachers.sort_by_key(|e| !e.primary.eq_ignore_ascii_case("true"));
+
+    for teacher in teachers {
+        let user = sqlx::query!("SELECT id FROM users WHERE sourced_id = $1", teacher.user_sourced_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        if let Some(user) = user {
+            return Ok(Some(user.id));
+        }
+    }
+
+    Ok(None)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn import_classes(
+    pool: &PgPool,
+    conn: &mut PgConnection,
+    rows: Vec<(usize, ClassRow)>,
+    orgs: Option<&HashSet<String>>,
+    enrollments: &[(usize, EnrollmentRow)],
+    report: &mut OneRosterImportReport,
+    touched: &mut HashSet<i32>,
+    importer_id: i32,
+) -> Result<(), (StatusCode, String)> {
+    for (row, class) in rows {
+        if class.sourced_id.is_empty() {
+            report.errors.push(import_error("classes.csv", row, "", "sourcedId is required".to_string()));
+            continue;
+        }
+
+        let existing = sqlx::query!(
+            "SELECT id, archived_at FROM classrooms WHERE sourced_id = $1",
+            class.sourced_id
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        // removed classes are archived so their grades stay available
+        if is_deleted(&class.status) {
+            match existing {
+                Some(existing) if existing.archived_at.is_none() => {
+                    sqlx::query!(
+                        "UPDATE classrooms SET archived_at = NOW(), updated_at = NOW() WHERE id = $1",
+                        existing.id
+                    )
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+                    report.classes_archived += 1;
+                }
+                _ => report.skipped += 1,
+            }
+            continue;
+        }
+
+        if class.title.is_empty() {
+            report.errors.push(import_error(
+                "classes.csv",
+                row,
+                &class.sourced_id,
+                "title is required".to_string(),
+            ));
+            continue;
+        }
+
+        if orgs.is_some_and(|orgs| !class.school_sourced_id.is_empty() && !orgs.contains(&class.school_sourced_id)) {
+            report.errors.push(import_error(
+                "classes.csv",
+                row,
+                &class.sourced_id,
+                format!("Unknown school `{}`", class.school_sourced_id),
+            ));
+            continue;
+        }
+
+        let org_sourced_id = (!class.school_sourced_id.is_empty()).then_some(class.school_sourced_id);
+
+        match existing {
+            Some(existing) => {
+                sqlx::query!(
+                    "UPDATE classrooms SET name = $1, org_sourced_id = $2, updated_at = NOW() WHERE id = $3",
+                    class.title,
+                    org_sourced_id,
+                    existing.id
+                )
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+                report.classes_updated += 1;
+            }
+            None => {
+                // the primary teacher owns the classroom, the importing admin only when there is none
+                let creator_id = class_creator(&mut *conn, &class.sourced_id, enrollments)
+                    .await?
+                    .unwrap_or(importer_id);
+                let join_code = unique_join_code(pool).await?;
+                let description = (!class.class_code.is_empty()).then_some(class.class_code);
+
+                let classroom = sqlx::query!(
+                    r#"
+                    INSERT INTO classrooms (id_base64, name, description, join_code, creator_id, sourced_id, org_sourced_id)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    RETURNING id
+                    "#,
+                    Uuid::new_v4().to_string(),
+                    class.title,
+                    description,
+                    join_code,
+                    creator_id,
+                    class.sourced_id,
+                    org_sourced_id
+                )
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+                sqlx::query!(
+                    "INSERT INTO user_classroom_roles (classroom_id, user_id, role) VALUES ($1, $2, $3)",
+                    classroom.id,
+                    creator_id,
+                    ClassroomRole::Creator.as_str()
+                )
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+                insert_grading_scale(&mut *conn, classroom.id, &GradingScale::default()).await?;
+
+                touched.insert(creator_id);
+                report.classes_created += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn import_enrollments(
+    conn: &mut PgConnection,
+    rows: &[(usize, EnrollmentRow)],
+    report: &mut OneRosterImportReport,
+    touched: &mut HashSet<i32>,
+) -> Result<(), (StatusCode, String)> {
+    for (row, enrollment) in rows {
+        let row = *row;
+
+        let classroom = sqlx::query!(
+            "SELECT id FROM classrooms WHERE sourced_id = $1",
+            enrollment.class_sourced_id
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let Some(classroom) = classroom else {
+            report.errors.push(import_error(
+                "enrollments.csv",
+                row,
+                &enrollment.sourced_id,
+                format!("Unknown class `{}`", enrollment.class_sourced_id),
+            ));
+            continue;
+        };
+
+        let user = sqlx::query!(
+            "SELECT id FROM users WHERE sourced_id = $1",
+            enrollment.user_sourced_id
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let Some(user) = user else {
+            report.errors.push(import_error(
+                "enrollments.csv",
+                row,
+                &enrollment.sourced_id,
+                format!("Unknown user `{}`", enrollment.user_sourced_id),
+            ));
+            continue;
+        };
+
+        if is_deleted(&enrollment.status) {
+            // the creator owns the classroom and is never removed by a sync
+            let removed = sqlx::query!(
+                "DELETE FROM user_classroom_roles WHERE classroom_id = $1 AND user_id = $2 AND role <> $3",
+                classroom.id,
+                user.id,
+                ClassroomRole::Creator.as_str()
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+            if removed.rows_affected() > 0 {
+                leave_groups(&mut *conn, classroom.id, user.id).await?;
+                report.enrollments_removed += 1;
+                touched.insert(user.id);
+            } else {
+                report.skipped += 1;
+            }
+            continue;
+        }
+
+        // administrators, aides, guardians and the like have no place in a classroom
+        let role = match enrollment.role.to_lowercase().as_str() {
+            "teacher" => ClassroomRole::Teacher,
+            "student" => ClassroomRole::Student,
+            _ => {
+                report.skipped += 1;
+                continue;
+            }
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO user_classroom_roles (user_id, classroom_id, role) VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, classroom_id) DO UPDATE SET role = EXCLUDED.role
+            WHERE user_classroom_roles.role <> $4
+            "#,
+            user.id,
+            classroom.id,
+            role.as_str(),
+            ClassroomRole::Creator.as_str()
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        touched.insert(user.id);
+        report.enrollments_saved += 1;
+    }
+
+    Ok(())
+}
+
+pub async fn import_oneroster(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Query(query): Query<OneRosterImportQuery>,
+    mut multipart: Multipart,
+) -> Result<Json<OneRosterImportReport>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT is_admin FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    match user {
+        Some(user) if user.is_admin => {}
+        Some(_) => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Only administrators can import OneRoster data".to_string(),
+            ))
+        }
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    }
+
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::BAD_REQUEST, "OneRoster bundle is missing".to_string()))?;
+    let data = field
+        .bytes()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let files = read_bundle(&data)?;
+    if !["users.csv", "classes.csv", "enrollments.csv"]
+        .iter()
+        .any(|name| files.contains_key(*name))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The bundle contains no users.csv, classes.csv or enrollments.csv".to_string(),
+        ));
+    }
+
+    let mut report = OneRosterImportReport {
+        dry_run: query.dry_run,
+        ..Default::default()
+    };
+
+    let orgs: Option<HashSet<String>> = read_rows::<OrgRow>(&files, "orgs.csv", &mut report)
+        .map(|rows| rows.into_iter().map(|(_, org)| org.sourced_id).collect());
+    report.orgs = orgs.as_ref().map_or(0, |orgs| orgs.len());
+
+    let users = read_rows::<UserRow>(&files, "users.csv", &mut report).unwrap_or_default();
+    let classes = read_rows::<ClassRow>(&files, "classes.csv", &mut report).unwrap_or_default();
+    let enrollments = read_rows::<EnrollmentRow>(&files, "enrollments.csv", &mut report).unwrap_or_default();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut touched = HashSet::new();
+
+    import_users(&mut tx, users, &mut report, claims.sub).await?;
+    import_classes(
+        &pool,
+        &mut tx,
+        classes,
+        orgs.as_ref(),
+        &enrollments,
+        &mut report,
+        &mut touched,
+        claims.sub,
+    )
+    .await?;
+    import_enrollments(&mut tx, &enrollments, &mut report, &mut touched).await?;
+
+    for user_id in touched {
+        touch_roles(&mut *tx, user_id).await?;
+    }
+
+    // a dry run goes through every write so the counts are exact, then rolls back
+    if query.dry_run {
+        tx.rollback()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        report.password_setup.clear();
+        return Ok(Json(report));
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    report.applied = true;
+
+    Ok(Json(report))
+}
+
+fn write_csv(header: &[&str], rows: Vec<Vec<String>>) -> Result<Vec<u8>, (StatusCode, String)> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(header)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for row in rows {
+        writer
+            .write_record(&row)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+const MANIFEST_FILES: [&str; 21] = [
+    "academicSessions",
+    "categories",
+    "classes",
+    "classResources",
+    "courses",
+    "courseResources",
+    "demographics",
+    "enrollments",
+    "lineItemLearningObjectiveIds",
+    "lineItems",
+    "lineItemScoreScales",
+    "orgs",
+    "resources",
+    "resultLearningObjectiveIds",
+    "results",
+    "resultScoreScales",
+    "roles",
+    "scoreScales",
+    "userProfiles",
+    "userResources",
+    "users",
+];
+
+fn manifest_csv() -> Result<Vec<u8>, (StatusCode, String)> {
+    let mut rows = vec![
+        vec!["manifest.version".to_string(), "1.0".to_string()],
+        vec!["oneroster.version".to_string(), "1.2".to_string()],
+    ];
+    rows.extend(MANIFEST_FILES.iter().map(|file| {
+        let mode = if matches!(*file, "lineItems" | "results") { "bulk" } else { "absent" };
+        vec![format!("file.{}", file), mode.to_string()]
+    }));
+    rows.push(vec!["source.systemName".to_string(), "sun-class".to_string()]);
+
+    write_csv(&["propertyName", "value"], rows)
+}
+
+pub async fn export_oneroster(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can export grades".to_string(),
+        ));
+    }
+
+    let classroom = sqlx::query!(
+        "SELECT sourced_id, org_sourced_id FROM classrooms WHERE id = $1",
+        c_id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let Some(class_sourced_id) = classroom.sourced_id else {
+        return Err((
+            StatusCode::CONFLICT,
+            "Classroom is not linked to a OneRoster class".to_string(),
+        ));
+    };
+    let school_sourced_id = classroom.org_sourced_id.unwrap_or_default();
+
+    let assignments = sqlx::query!(
+        "SELECT id, id_base64, title, description, due_date, points, created_at FROM assignments WHERE classroom_id = $1 ORDER BY position, id",
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // only students known to the SIS can be reported back to it
+    let results = sqlx::query!(
+        r#"
+        SELECT a.id_base64, u.sourced_id AS "sourced_id!", s.grade, s.status AS "status: SubmissionStatus",
+            s.feedback, s.submitted_at, s.graded_at
+        FROM submissions s
+        JOIN assignments a ON s.assignment_id = a.id
+        JOIN users u ON s.user_id = u.id
+        JOIN user_classroom_roles uc ON uc.user_id = u.id AND uc.classroom_id = a.classroom_id
+        WHERE a.classroom_id = $1 AND uc.role = 'student' AND u.sourced_id IS NOT NULL
+        ORDER BY a.position, a.id, u.sourced_id
+        "#,
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // bulk files leave status and dateLastModified empty
+    let line_items = write_csv(
+        &[
+            "sourcedId",
+            "status",
+            "dateLastModified",
+            "title",
+            "description",
+            "assignDate",
+            "dueDate",
+            "classSourcedId",
+            "categorySourcedId",
+            "academicSessionSourcedId",
+            "schoolSourcedId",
+            "resultValueMin",
+            "resultValueMax",
+        ],
+        assignments
+            .iter()
+            .map(|a| {
+                vec![
+                    a.id_base64.clone(),
+                    String::new(),
+                    String::new(),
+                    a.title.clone(),
+                    a.description.clone().unwrap_or_default(),
+                    a.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    a.due_date
+                        .map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true))
+                        .unwrap_or_default(),
+                    class_sourced_id.clone(),
+                    String::new(),
+                    String::new(),
+                    school_sourced_id.clone(),
+                    "0".to_string(),
+                    a.points.unwrap_or(0).to_string(),
+                ]
+            })
+            .collect(),
+    )?;
+
+    let results = write_csv(
+        &[
+            "sourcedId",
+            "status",
+            "dateLastModified",
+            "lineItemSourcedId",
+            "studentSourcedId",
+            "classSourcedId",
+            "scoreStatus",
+            "score",
+            "scoreDate",
+            "comment",
+        ],
+        results
+            .into_iter()
+            .map(|r| {
+                let score_status = match (r.grade, r.status.is_turned_in()) {
+                    (Some(_), _) => "fully graded",
+                    (None, true) => "submitted",
+                    (None, false) => "not submitted",
+                };
+                vec![
+                    format!("{}-{}", r.id_base64, r.sourced_id),
+                    String::new(),
+                    String::new(),
+                    r.id_base64,
+                    r.sourced_id,
+                    class_sourced_id.clone(),
+                    score_status.to_string(),
+                    r.grade.map(|g| g.to_string()).unwrap_or_default(),
+                    r.graded_at
+                        .or(r.submitted_at)
+                        .map(|d| d.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default(),
+                    r.feedback.unwrap_or_default(),
+                ]
+            })
+            .collect(),
+    )?;
+
+    let mut bundle = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in [
+        ("manifest.csv", manifest_csv()?),
+        ("lineItems.csv", line_items),
+        ("results.csv", results),
+    ] {
+        bundle
+            .start_file(name, zip::write::SimpleFileOptions::default())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        bundle
+            .write_all(&contents)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    let contents = bundle
+        .finish()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_inner();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, "application/zip".parse().unwrap());
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"oneroster_{}.zip\"", c_id)
+            .parse()
+            .unwrap(),
+    );
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .body(Body::from(contents))
+        .unwrap();
+
+    let (mut parts, body) = response.into_parts();
+    parts.headers = headers;
+
+    Ok(Response::from_parts(parts, body))
+}
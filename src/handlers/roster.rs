@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::body::Body;
+use axum::extract::{Path, Query};
+use axum::http::{HeaderMap, Response, header};
+use axum::{Json, extract::Multipart, extract::State, http::StatusCode};
+use chrono::SecondsFormat;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tower_cookies::Cookies;
+
+use crate::dto::{MemberRole, RosterImportQuery, RosterImportReport, RosterImportRow, RosterRowStatus};
+use crate::handlers::auth::issue_password_setup_token;
+use crate::handlers::classroom::{check_not_archived, classroom_role, csv_cell};
+use crate::handlers::members::touch_roles;
+use crate::middlewares::jwt::check_auth;
+use crate::models::ClassroomRole;
+
+#[derive(Deserialize)]
+struct RosterCsvRow {
+    #[serde(default)]
+    name: String,
+    email: String,
+    #[serde(default)]
+    role: String,
+}
+
+fn parse_member_role(role: &str) -> Option<MemberRole> {
+    match role.trim().to_lowercase().as_str() {
+        "" | "student" => Some(MemberRole::Student),
+        // exported rosters list the creator, who can only be added elsewhere as a teacher
+        "teacher" | "creator" => Some(MemberRole::Teacher),
+        _ => None,
+    }
+}
+
+struct PlannedMember {
+    result: usize,
+    user_id: Option<i32>,
+    name: String,
+    email: String,
+    role: MemberRole,
+}
+
+pub async fn import_roster(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Query(query): Query<RosterImportQuery>,
+    mut multipart: Multipart,
+) -> Result<Json<RosterImportReport>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let role = classroom_role(&pool, c_id, claims.sub).await?;
+    if !role.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can import the roster".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or((StatusCode::BAD_REQUEST, "CSV file is missing".to_string()))?;
+    let data = field
+        .bytes()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(data.as_ref());
+    let records: Vec<_> = reader.deserialize::<RosterCsvRow>().collect();
+
+    let emails: Vec<String> = records
+        .iter()
+        .filter_map(|r| r.as_ref().ok())
+        .map(|r| r.email.to_lowercase())
+        .collect();
+
+    let existing_users: HashMap<String, i32> = sqlx::query!(
+        "SELECT id, LOWER(email) AS \"email!\" FROM users WHERE LOWER(email) = ANY($1)",
+        &emails
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .into_iter()
+    .map(|u| (u.email, u.id))
+    .collect();
+
+    let members: HashSet<i32> = sqlx::query!(
+        "SELECT user_id FROM user_classroom_roles WHERE classroom_id = $1",
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .into_iter()
+    .map(|m| m.user_id)
+    .collect();
+
+    // accounts that never set a password can get a fresh link, but only from teachers of the
+    // classroom whose import created them, so other teachers can't take them over
+    let existing_ids: Vec<i32> = existing_users.values().copied().collect();
+    let reissuable: HashSet<i32> = sqlx::query!(
+        r#"
+        SELECT DISTINCT t.user_id
+        FROM password_setup_tokens t
+        JOIN users u ON t.user_id = u.id
+        JOIN user_classroom_roles uc ON uc.user_id = t.created_by
+        WHERE uc.classroom_id = $1 AND uc.role IN ('creator', 'teacher')
+        AND u.password_hash = '' AND t.user_id = ANY($2)
+        "#,
+        c_id,
+        &existing_ids
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .into_iter()
+    .map(|t| t.user_id)
+    .collect();
+
+    let mut report = RosterImportReport {
+        dry_run: query.dry_run,
+        applied: false,
+        total_rows: records.len(),
+        created: 0,
+        enrolled: 0,
+        already_enrolled: 0,
+        errors: 0,
+        rows: Vec::new(),
+    };
+    let mut planned = Vec::new();
+    let mut reissued = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (index, record) in records.into_iter().enumerate() {
+        // header is line 1
+        let row = index + 2;
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                report.rows.push(RosterImportRow {
+                    row,
+                    email: String::new(),
+                    role: None,
+                    status: RosterRowStatus::Error,
+                    message: Some(e.to_string()),
+                    setup_token: None,
+                });
+                continue;
+            }
+        };
+
+        let email = record.email.to_lowercase();
+        let member_role = parse_member_role(&record.role);
+        let user_id = existing_users.get(&email).copied();
+
+        let error = if !email.contains('@') {
+            Some(format!("Invalid email address `{}`", record.email))
+        } else if member_role.is_none() {
+            Some(format!("Invalid role `{}`, expected student or teacher", record.role))
+        } else if let Some(first_row) = seen.get(&email) {
+            Some(format!("Duplicate email, already listed on row {}", first_row))
+        } else if user_id.is_none() && record.name.is_empty() {
+            Some("A name is required to create a new user".to_string())
+        } else if member_role == Some(MemberRole::Teacher)
+            && role != ClassroomRole::Creator
+            && !user_id.is_some_and(|id| members.contains(&id))
+        {
+            Some("Only the classroom creator can add teachers".to_string())
+        } else {
+            None
+        };
+        seen.entry(email.clone()).or_insert(row);
+
+        let status = match (&error, user_id) {
+            (Some(_), _) => RosterRowStatus::Error,
+            (None, Some(id)) if members.contains(&id) => RosterRowStatus::AlreadyEnrolled,
+            (None, Some(_)) => RosterRowStatus::Enrolled,
+            (None, None) => RosterRowStatus::Created,
+        };
+
+        match status {
+            RosterRowStatus::Created => report.created += 1,
+            RosterRowStatus::Enrolled => report.enrolled += 1,
+            RosterRowStatus::AlreadyEnrolled => report.already_enrolled += 1,
+            RosterRowStatus::Error => report.errors += 1,
+        }
+
+        if let (RosterRowStatus::Created | RosterRowStatus::Enrolled, Some(member_role)) = (status, member_role) {
+            planned.push(PlannedMember {
+                result: report.rows.len(),
+                user_id,
+                name: record.name,
+                email: email.clone(),
+                role: member_role,
+            });
+        }
+
+        let message = match (status, user_id) {
+            (RosterRowStatus::AlreadyEnrolled, Some(id)) if reissuable.contains(&id) => {
+                reissued.push((report.rows.len(), id));
+                Some("User has not set a password yet, a new setup link is issued".to_string())
+            }
+            _ => error,
+        };
+
+        report.rows.push(RosterImportRow {
+            row,
+            email,
+            role: member_role,
+            status,
+            message,
+            setup_token: None,
+        });
+    }
+
+    if query.dry_run || (planned.is_empty() && reissued.is_empty()) {
+        return Ok(Json(report));
+    }
+
+    // rows with errors are reported and skipped, every other row is applied together
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for member in planned {
+        let user_id = match member.user_id {
+            Some(user_id) => {
+                touch_roles(&mut *tx, user_id).await?;
+                user_id
+            }
+            None => {
+                let user = sqlx::query!(
+                    "INSERT INTO users (name, email, password_hash) VALUES ($1, $2, '') RETURNING id",
+                    member.name,
+                    member.email
+                )
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+                let token = issue_password_setup_token(&mut tx, user.id, claims.sub).await?;
+                report.rows[member.result].setup_token = Some(token);
+                user.id
+            }
+        };
+
+        let role = match member.role {
+            MemberRole::Teacher => ClassroomRole::Teacher,
+            MemberRole::Student => ClassroomRole::Student,
+        };
+
+        sqlx::query!(
+            "INSERT INTO user_classroom_roles (user_id, classroom_id, role) VALUES ($1, $2, $3)",
+            user_id,
+            c_id,
+            role.as_str()
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    for (result, user_id) in reissued {
+        let token = issue_password_setup_token(&mut tx, user_id, claims.sub).await?;
+        report.rows[result].setup_token = Some(token);
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    report.applied = true;
+
+    Ok(Json(report))
+}
+
+pub async fn export_roster(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can export the roster".to_string(),
+        ));
+    }
+
+    let members = sqlx::query!(
+        r#"
+        SELECT u.name, u.email, uc.role AS "role: ClassroomRole", uc.joined_at
+        FROM user_classroom_roles uc
+        JOIN users u ON uc.user_id = u.id
+        WHERE uc.classroom_id = $1
+        ORDER BY CASE uc.role WHEN 'creator' THEN 0 WHEN 'teacher' THEN 1 ELSE 2 END, u.name, u.id
+        "#,
+        c_id
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["name", "email", "role", "joined_at"])
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for member in members {
+        writer
+            .write_record([
+                csv_cell(&member.name),
+                csv_cell(&member.email),
+                member.role.as_str().to_string(),
+                member.joined_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ])
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+
+    let contents = writer
+        .into_inner()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, "text/csv; charset=utf-8".parse().unwrap());
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"roster_{}.csv\"", c_id)
+            .parse()
+            .unwrap(),
+    );
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .body(Body::from(contents))
+        .unwrap();
+
+    let (mut parts, body) = response.into_parts();
+    parts.headers = headers;
+
+    Ok(Response::from_parts(parts, body))
+}
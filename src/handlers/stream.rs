@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use axum::body::Body;
+use axum::extract::{Path, Query};
+use axum::http::{HeaderMap, Response, header};
+use axum::{Json, extract::Multipart, extract::State, http::StatusCode};
+use sqlx::PgPool;
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+use crate::dto::{
+    AnnouncementAttachment, AnnouncementInfo, AnnouncementRequest, CommentInfo, CommentRequest,
+    CreatedAnnouncementResponse, StreamQuery, StreamResponse,
+};
+use crate::handlers::classroom::{check_not_archived, classroom_role};
+use crate::middlewares::jwt::check_auth;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+fn validate_body(body: &str) -> Result<(), (StatusCode, String)> {
+    if body.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Text cannot be empty".to_string()));
+    }
+    if body.len() > 20_000 {
+        return Err((StatusCode::BAD_REQUEST, "Text is too long".to_string()));
+    }
+    Ok(())
+}
+
+pub async fn get_stream(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Query(query): Query<StreamQuery>,
+) -> Result<Json<StreamResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    classroom_role(&pool, c_id, claims.sub).await?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = (page - 1)
+        .checked_mul(per_page)
+        .ok_or((StatusCode::BAD_REQUEST, "Page is out of range".to_string()))?;
+
+    let total = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM announcements WHERE classroom_id = $1"#,
+        c_id
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .count;
+
+    let announcements = sqlx::query!(
+        r#"
+        SELECT an.id, an.author_id, u.name AS author_name, an.body, an.created_at, an.updated_at
+        FROM announcements an
+        JOIN users u ON an.author_id = u.id
+        WHERE an.classroom_id = $1
+        ORDER BY an.created_at DESC, an.id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        c_id,
+        per_page,
+        offset
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let ids: Vec<i32> = announcements.iter().map(|a| a.id).collect();
+
+    let attachments = sqlx::query!(
+        "SELECT id, announcement_id, file_name, content_type FROM announcement_files WHERE announcement_id = ANY($1) ORDER BY id",
+        &ids
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let comments = sqlx::query!(
+        r#"
+        SELECT cm.id, cm.announcement_id, cm.user_id, u.name AS author_name, cm.body, cm.created_at
+        FROM announcement_comments cm
+        JOIN users u ON cm.user_id = u.id
+        WHERE cm.announcement_id = ANY($1)
+        ORDER BY cm.created_at, cm.id
+        "#,
+        &ids
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut attachments_by_announcement: HashMap<i32, Vec<AnnouncementAttachment>> = HashMap::new();
+    for file in attachments {
+        attachments_by_announcement
+            .entry(file.announcement_id)
+            .or_default()
+            .push(AnnouncementAttachment {
+                id: file.id,
+                file_name: file.file_name,
+                content_type: file.content_type.unwrap_or("unknown".to_string()),
+            });
+    }
+
+    let mut comments_by_announcement: HashMap<i32, Vec<CommentInfo>> = HashMap::new();
+    for comment in comments {
+        comments_by_announcement
+            .entry(comment.announcement_id)
+            .or_default()
+            .push(CommentInfo {
+                id: comment.id,
+                author_id: comment.user_id,
+                author_name: comment.author_name,
+                body: comment.body,
+                created_at: comment.created_at.to_string(),
+            });
+    }
+
+    let announcements: Vec<AnnouncementInfo> = announcements
+        .into_iter()
+        .map(|a| AnnouncementInfo {
+            attachments: attachments_by_announcement.remove(&a.id).unwrap_or_default(),
+            comments: comments_by_announcement.remove(&a.id).unwrap_or_default(),
+            id: a.id,
+            author_id: a.author_id,
+            author_name: a.author_name,
+            body: a.body,
+            created_at: a.created_at.to_string(),
+            updated_at: a.updated_at.to_string(),
+        })
+        .collect();
+
+    Ok(Json(StreamResponse {
+        announcements,
+        page,
+        per_page,
+        total,
+        has_more: offset.saturating_add(per_page) < total,
+    }))
+}
+
+pub async fn create_announcement(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path(c_id): Path<i32>,
+    Json(request): Json<AnnouncementRequest>,
+) -> Result<Json<CreatedAnnouncementResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can post announcements".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+    validate_body(&request.body)?;
+
+    let announcement = sqlx::query!(
+        "INSERT INTO announcements (classroom_id, author_id, body) VALUES ($1, $2, $3) RETURNING id",
+        c_id,
+        claims.sub,
+        request.body
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(CreatedAnnouncementResponse {
+        id: announcement.id,
+    }))
+}
+
+pub async fn update_announcement(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, an_id)): Path<(i32, i32)>,
+    Json(request): Json<AnnouncementRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can edit announcements".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+    validate_body(&request.body)?;
+
+    let updated = sqlx::query!(
+        "UPDATE announcements SET body = $1, updated_at = NOW() WHERE id = $2 AND classroom_id = $3",
+        request.body,
+        an_id,
+        c_id
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if updated.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Announcement not found".to_string()));
+    }
+
+    Ok("Announcement updated successfully".to_string())
+}
+
+pub async fn delete_announcement(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, an_id)): Path<(i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can delete announcements".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let files = sqlx::query!(
+        "SELECT file_path FROM announcement_files WHERE announcement_id = $1",
+        an_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // attachments and comments are removed by cascade
+    let deleted = sqlx::query!(
+        "DELETE FROM announcements WHERE id = $1 AND classroom_id = $2",
+        an_id,
+        c_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if deleted.rows_affected() == 0 {
+        return Err((StatusCode::NOT_FOUND, "Announcement not found".to_string()));
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    for file in files {
+        let _ = std::fs::remove_file(file.file_path);
+    }
+
+    Ok("Announcement deleted successfully".to_string())
+}
+
+pub async fn add_announcement_attachments(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, an_id)): Path<(i32, i32)>,
+    mut multipart: Multipart,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    if !classroom_role(&pool, c_id, claims.sub).await?.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only teachers can attach files to announcements".to_string(),
+        ));
+    }
+
+    check_not_archived(&pool, c_id).await?;
+
+    let announcement = sqlx::query!(
+        "SELECT id FROM announcements WHERE id = $1 AND classroom_id = $2",
+        an_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if announcement.is_none() {
+        return Err((StatusCode::NOT_FOUND, "Announcement not found".to_string()));
+    }
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+    {
+        let filename = field.file_name().unwrap_or("unknown").to_string();
+        let content_type = field
+            .content_type()
+            .map_or("unknown".to_string(), |ct| ct.to_string());
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+        let unique_name = format!("{}_{}", Uuid::new_v4(), filename);
+        let path = format!("./uploads/{}", unique_name);
+
+        let mut file =
+            File::create(&path).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        file.write_all(&data)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        sqlx::query!(
+            "INSERT INTO announcement_files (announcement_id, user_id, file_name, file_path, content_type)
+             VALUES ($1, $2, $3, $4, $5)",
+            an_id,
+            claims.sub,
+            filename,
+            path,
+            content_type
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&path);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+    }
+
+    Ok("Announcement attachments saved successfully".to_string())
+}
+
+pub async fn download_announcement_file(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, f_id)): Path<(i32, i32)>,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    classroom_role(&pool, c_id, claims.sub).await?;
+
+    let file = sqlx::query!(
+        r#"
+        SELECT af.file_name, af.file_path, af.content_type
+        FROM announcement_files af
+        JOIN announcements an ON af.announcement_id = an.id
+        WHERE af.id = $1 AND an.classroom_id = $2
+        "#,
+        f_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let file = match file {
+        Some(file) => file,
+        None => return Err((StatusCode::NOT_FOUND, "File not found".to_string())),
+    };
+
+    let mut file_handle = File::open(&file.file_path).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to open file: {}", e),
+        )
+    })?;
+
+    let mut contents = Vec::new();
+    file_handle.read_to_end(&mut contents).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read file: {}", e),
+        )
+    })?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        file.content_type
+            .unwrap_or("application/octet-stream".to_string())
+            .parse()
+            .unwrap(),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}\"", file.file_name)
+            .parse()
+            .unwrap(),
+    );
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .body(Body::from(contents))
+        .unwrap();
+
+    let (mut parts, body) = response.into_parts();
+    parts.headers = headers;
+
+    Ok(Response::from_parts(parts, body))
+}
+
+pub async fn create_comment(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, an_id)): Path<(i32, i32)>,
+    Json(request): Json<CommentRequest>,
+) -> Result<Json<CommentInfo>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    classroom_role(&pool, c_id, claims.sub).await?;
+    check_not_archived(&pool, c_id).await?;
+    validate_body(&request.body)?;
+
+    let announcement = sqlx::query!(
+        "SELECT id FROM announcements WHERE id = $1 AND classroom_id = $2",
+        an_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if announcement.is_none() {
+        return Err((StatusCode::NOT_FOUND, "Announcement not found".to_string()));
+    }
+
+    let comment = sqlx::query!(
+        r#"
+        WITH inserted AS (
+            INSERT INTO announcement_comments (announcement_id, user_id, body)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, body, created_at
+        )
+        SELECT i.id, i.user_id, u.name AS author_name, i.body, i.created_at
+        FROM inserted i
+        JOIN users u ON i.user_id = u.id
+        "#,
+        an_id,
+        claims.sub,
+        request.body
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(CommentInfo {
+        id: comment.id,
+        author_id: comment.user_id,
+        author_name: comment.author_name,
+        body: comment.body,
+        created_at: comment.created_at.to_string(),
+    }))
+}
+
+pub async fn delete_comment(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Path((c_id, an_id, cm_id)): Path<(i32, i32, i32)>,
+) -> Result<String, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let role = classroom_role(&pool, c_id, claims.sub).await?;
+    check_not_archived(&pool, c_id).await?;
+
+    let comment = sqlx::query!(
+        r#"
+        SELECT cm.user_id FROM announcement_comments cm
+        JOIN announcements an ON cm.announcement_id = an.id
+        WHERE cm.id = $1 AND an.id = $2 AND an.classroom_id = $3
+        "#,
+        cm_id,
+        an_id,
+        c_id
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let comment = match comment {
+        Some(comment) => comment,
+        None => return Err((StatusCode::NOT_FOUND, "Comment not found".to_string())),
+    };
+
+    // teachers moderate the stream, everyone else can only remove their own comments
+    if comment.user_id != claims.sub && !role.is_teacher() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "You can only delete your own comments".to_string(),
+        ));
+    }
+
+    sqlx::query!("DELETE FROM announcement_comments WHERE id = $1", cm_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok("Comment deleted successfully".to_string())
+}
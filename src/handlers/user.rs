@@ -1,13 +1,19 @@
+use std::collections::HashMap;
+
 use axum::response::IntoResponse;
+use axum::extract::Query;
 use axum::{Json, extract::State, http::StatusCode};
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use tower_cookies::Cookies;
 
 use crate::dto::{
-    ClassroomListResponse, EditProfileRequest, UserClassroomResponse, UserProfileResponse,
+    CategoryGrade, ClassroomListResponse, CourseGrade, EditProfileRequest, TodoAssignment, TodoFilter, TodoQuery,
+    TodoState, UserAssignmentGrade, UserClassGrades, UserClassroomResponse, UserProfileResponse,
 };
+use crate::handlers::auth::refresh_auth_cookie;
 use crate::middlewares::jwt::check_auth;
+use crate::models::{weighted_percentage, GradeBand, GradingCategory, GradingScale, ScoredAssignment, SubmissionStatus};
 
 #[derive(sqlx::FromRow)]
 #[allow(dead_code)]
@@ -20,25 +26,43 @@ struct FUCKEDClassroom {
     due_date: Option<DateTime<Utc>>,
 }
 
+#[derive(sqlx::FromRow)]
+struct StudentAssignment {
+    classroom_id: i32,
+    class_title: String,
+    id: i32,
+    title: String,
+    points: Option<i32>,
+    due_date: Option<DateTime<Utc>>,
+    grade: Option<i32>,
+    status: Option<SubmissionStatus>,
+    submitted_at: Option<DateTime<Utc>>,
+}
+
 pub async fn get_user(
     State(pool): State<PgPool>,
     cookies: Cookies,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let claims = check_auth(cookies).await?;
+    let claims = check_auth(cookies.clone()).await?;
 
     let user = sqlx::query!(
-        "SELECT id, name, email FROM users WHERE id = $1",
+        "SELECT id, name, email, roles_updated_at FROM users WHERE id = $1",
         claims.sub
     )
     .fetch_optional(&pool)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let _user = match user {
+    let user = match user {
         Some(user) => user,
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
+    // memberships changed by someone else since the token was issued
+    if claims.iat as i64 <= user.roles_updated_at.timestamp() {
+        refresh_auth_cookie(&pool, &cookies, user.id).await?;
+    }
+
     Ok(StatusCode::OK)
 }
 
@@ -102,8 +126,29 @@ pub async fn list_classes(
 ) -> Result<Json<UserClassroomResponse>, (StatusCode, String)> {
     let claims = check_auth(cookies).await?;
 
-    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
-        .fetch_optional(&pool)
+    let classes = load_classes(&pool, claims.sub, false).await?;
+
+    Ok(Json(classes))
+}
+
+pub async fn list_archived_classes(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+) -> Result<Json<UserClassroomResponse>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let classes = load_classes(&pool, claims.sub, true).await?;
+
+    Ok(Json(classes))
+}
+
+async fn load_classes(
+    pool: &PgPool,
+    user_id: i32,
+    archived: bool,
+) -> Result<UserClassroomResponse, (StatusCode, String)> {
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -112,39 +157,36 @@ pub async fn list_classes(
         None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
     };
 
-    let enrolled_classrooms = sqlx::query_as!(
-        FUCKEDClassroom,
+    let enrolled_classrooms = sqlx::query!(
         "SELECT 
         c.id,
         c.name AS title,
-        u.name AS teacher,
-        COALESCE(a.id, NULL) AS assignment_id,
-        COALESCE(a.title, NULL) AS assignment_title,
-        COALESCE(a.due_date, NULL) AS due_date
+        u.name AS teacher
     FROM classrooms c
     JOIN users u ON c.creator_id = u.id
     JOIN user_classroom_roles uc ON uc.classroom_id = c.id
-    LEFT JOIN LATERAL (
-        SELECT id, title, due_date
-        FROM assignments
-        WHERE assignments.classroom_id = c.id AND due_date > NOW()
-        ORDER BY due_date ASC
-        LIMIT 1
-    ) a ON true
-    WHERE uc.user_id = $1 AND uc.role = 'student'",
-        claims.sub
+    WHERE uc.user_id = $1 AND uc.role = 'student' AND (c.archived_at IS NOT NULL) = $2",
+        user_id,
+        archived
     )
-    .fetch_all(&pool)
+    .fetch_all(pool)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    // student assignments come sorted by effective due date, so the first future one is the upcoming one
+    let now = Utc::now();
+    let student_assignments = load_student_assignments(pool, user_id, archived).await?;
+
     let enrolled_classes: Vec<ClassroomListResponse> = enrolled_classrooms
         .into_iter()
         .map(|c| ClassroomListResponse {
+            upcoming_assignment: student_assignments
+                .iter()
+                .find(|a| a.classroom_id == c.id && a.due_date.is_some_and(|due| due > now))
+                .map(|a| a.title.clone()),
             id: c.id,
             title: c.title,
             teacher: c.teacher,
-            upcoming_assignment: c.assignment_title,
         })
         .collect();
 
@@ -160,6 +202,7 @@ pub async fn list_classes(
         COALESCE(a.due_date, NULL) AS due_date
     FROM classrooms c
     JOIN users u ON c.creator_id = u.id
+    JOIN user_classroom_roles uc ON uc.classroom_id = c.id
     LEFT JOIN LATERAL (
         SELECT id, title, due_date
         FROM assignments
@@ -167,11 +210,12 @@ pub async fn list_classes(
         ORDER BY due_date ASC
         LIMIT 1
     ) a ON true
-    WHERE c.creator_id = $1
+    WHERE uc.user_id = $1 AND uc.role IN ('creator', 'teacher') AND (c.archived_at IS NOT NULL) = $2
     "#,
-        claims.sub
+        user_id,
+        archived
     )
-    .fetch_all(&pool)
+    .fetch_all(pool)
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -185,8 +229,282 @@ pub async fn list_classes(
         })
         .collect();
 
-    Ok(Json(UserClassroomResponse {
+    Ok(UserClassroomResponse {
         enrolled_classes,
         teaching_classes,
-    }))
+    })
+}
+
+pub async fn list_grades(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+) -> Result<Json<Vec<UserClassGrades>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let classes = sqlx::query!(
+        r#"
+        SELECT c.id, c.name AS title, u.name AS teacher, c.archived_at
+        FROM classrooms c
+        JOIN users u ON c.creator_id = u.id
+        JOIN user_classroom_roles uc ON uc.classroom_id = c.id
+        WHERE uc.user_id = $1 AND uc.role = 'student'
+        ORDER BY c.archived_at IS NOT NULL, c.name, c.id
+        "#,
+        claims.sub
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let assignments = sqlx::query!(
+        r#"
+        SELECT
+            a.classroom_id,
+            a.id,
+            a.title,
+            a.points,
+            a.category_id,
+            COALESCE(ext.due_date, a.due_date) AS due_date,
+            s.grade AS "grade?",
+            s.status AS "status?: SubmissionStatus",
+            s.submitted_at AS "submitted_at?"
+        FROM assignments a
+        JOIN user_classroom_roles uc ON uc.classroom_id = a.classroom_id
+        LEFT JOIN submissions s ON s.assignment_id = a.id AND s.user_id = uc.user_id
+        LEFT JOIN assignment_extensions ext ON ext.assignment_id = a.id AND ext.user_id = uc.user_id
+        WHERE uc.user_id = $1 AND uc.role = 'student'
+        AND assignment_visible_to(a.id, uc.user_id)
+        ORDER BY a.classroom_id, a.position, a.id
+        "#,
+        claims.sub
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let categories = sqlx::query_as!(
+        GradingCategory,
+        r#"
+        SELECT gc.id, gc.classroom_id, gc.name, gc.weight, gc.drop_lowest, gc.created_at, gc.updated_at
+        FROM grading_categories gc
+        JOIN user_classroom_roles uc ON uc.classroom_id = gc.classroom_id
+        WHERE uc.user_id = $1 AND uc.role = 'student'
+        ORDER BY gc.id
+        "#,
+        claims.sub
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let bands = sqlx::query!(
+        r#"
+        SELECT b.classroom_id, b.label, b.min_percentage
+        FROM grading_scale_bands b
+        JOIN user_classroom_roles uc ON uc.classroom_id = b.classroom_id
+        WHERE uc.user_id = $1 AND uc.role = 'student'
+        "#,
+        claims.sub
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut categories_by_class: HashMap<i32, Vec<GradingCategory>> = HashMap::new();
+    for category in categories {
+        categories_by_class
+            .entry(category.classroom_id)
+            .or_default()
+            .push(category);
+    }
+
+    let mut bands_by_class: HashMap<i32, Vec<GradeBand>> = HashMap::new();
+    for band in bands {
+        bands_by_class.entry(band.classroom_id).or_default().push(GradeBand {
+            label: band.label,
+            min_percentage: band.min_percentage,
+        });
+    }
+
+    let mut assignments_by_class: HashMap<i32, Vec<_>> = HashMap::new();
+    for assignment in assignments {
+        assignments_by_class
+            .entry(assignment.classroom_id)
+            .or_default()
+            .push(assignment);
+    }
+
+    let grades = classes
+        .into_iter()
+        .map(|c| {
+            let categories = categories_by_class.remove(&c.id).unwrap_or_default();
+            let scale = GradingScale::new(bands_by_class.remove(&c.id).unwrap_or_default());
+            let assignments = assignments_by_class.remove(&c.id).unwrap_or_default();
+
+            let scores: Vec<ScoredAssignment> = assignments
+                .iter()
+                .filter_map(|a| {
+                    a.grade.map(|grade| ScoredAssignment {
+                        category_id: a.category_id,
+                        grade,
+                        points: a.points.unwrap_or(0),
+                    })
+                })
+                .collect();
+
+            let earned_points: i64 = scores.iter().map(|s| s.grade as i64).sum();
+            let graded_points: i64 = scores.iter().map(|s| s.points as i64).sum();
+            let total_points: i64 = assignments.iter().map(|a| a.points.unwrap_or(0) as i64).sum();
+            let percentage = (graded_points > 0).then(|| 100.0 * earned_points as f64 / graded_points as f64);
+            let weighted_percentage = weighted_percentage(&categories, &scores);
+
+            let category_grades = categories
+                .iter()
+                .map(|category| CategoryGrade {
+                    category_id: category.id,
+                    name: category.name.clone(),
+                    weight: category.weight,
+                    percentage: category.percentage(&scores),
+                })
+                .collect();
+
+            let assignments = assignments
+                .into_iter()
+                .map(|a| {
+                    let status = a.status.unwrap_or(SubmissionStatus::Assigned);
+                    let points = a.points.unwrap_or(0);
+                    UserAssignmentGrade {
+                        assignment_id: a.id,
+                        title: a.title,
+                        due_date: a.due_date.map(|dt| dt.to_string()),
+                        points,
+                        category_id: a.category_id,
+                        grade: a.grade,
+                        display_grade: scale.display_grade(a.grade, points),
+                        status,
+                        is_late: status.is_late(a.submitted_at, a.due_date),
+                        is_missing: status.is_missing(a.due_date),
+                    }
+                })
+                .collect();
+
+            UserClassGrades {
+                class_id: c.id,
+                class_title: c.title,
+                teacher: c.teacher,
+                is_archived: c.archived_at.is_some(),
+                total_points,
+                grade: CourseGrade {
+                    earned_points,
+                    graded_points,
+                    percentage,
+                    weighted_percentage,
+                    display_grade: weighted_percentage.or(percentage).and_then(|p| scale.label_for(p)),
+                    category_grades,
+                },
+                assignments,
+            }
+        })
+        .collect();
+
+    Ok(Json(grades))
+}
+
+async fn load_student_assignments(
+    pool: &PgPool,
+    user_id: i32,
+    archived: bool,
+) -> Result<Vec<StudentAssignment>, (StatusCode, String)> {
+    sqlx::query_as!(
+        StudentAssignment,
+        r#"
+        SELECT
+            c.id AS classroom_id,
+            c.name AS class_title,
+            a.id,
+            a.title,
+            a.points,
+            COALESCE(ext.due_date, a.due_date) AS due_date,
+            s.grade AS "grade?",
+            s.status AS "status?: SubmissionStatus",
+            s.submitted_at AS "submitted_at?"
+        FROM assignments a
+        JOIN classrooms c ON a.classroom_id = c.id
+        JOIN user_classroom_roles uc ON uc.classroom_id = c.id
+        LEFT JOIN submissions s ON s.assignment_id = a.id AND s.user_id = uc.user_id
+        LEFT JOIN assignment_extensions ext ON ext.assignment_id = a.id AND ext.user_id = uc.user_id
+        WHERE uc.user_id = $1 AND uc.role = 'student' AND (c.archived_at IS NOT NULL) = $2
+        AND assignment_visible_to(a.id, uc.user_id)
+        ORDER BY COALESCE(ext.due_date, a.due_date) ASC NULLS LAST, c.name, a.position, a.id
+        "#,
+        user_id,
+        archived
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+pub async fn list_assignments(
+    State(pool): State<PgPool>,
+    cookies: Cookies,
+    Query(query): Query<TodoQuery>,
+) -> Result<Json<Vec<TodoAssignment>>, (StatusCode, String)> {
+    let claims = check_auth(cookies).await?;
+
+    let user = sqlx::query!("SELECT id FROM users WHERE id = $1", claims.sub)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let _user = match user {
+        Some(user) => user,
+        None => return Err((StatusCode::NOT_FOUND, "User not found".to_string())),
+    };
+
+    let assignments = load_student_assignments(&pool, claims.sub, false).await?;
+
+    let todo = assignments
+        .into_iter()
+        .filter(|a| query.class_id.is_none_or(|class_id| a.classroom_id == class_id))
+        .map(|a| {
+            let status = a.status.unwrap_or(SubmissionStatus::Assigned);
+            let state = match status {
+                SubmissionStatus::Returned => TodoState::Graded,
+                SubmissionStatus::TurnedIn | SubmissionStatus::Resubmitted => TodoState::TurnedIn,
+                SubmissionStatus::Assigned if status.is_missing(a.due_date) => TodoState::Missing,
+                SubmissionStatus::Assigned => TodoState::NotSubmitted,
+            };
+            TodoAssignment {
+                assignment_id: a.id,
+                class_id: a.classroom_id,
+                class_title: a.class_title,
+                title: a.title,
+                due_date: a.due_date.map(|dt| dt.to_string()),
+                points: a.points.unwrap_or(0),
+                grade: a.grade,
+                state,
+                status,
+                is_late: status.is_late(a.submitted_at, a.due_date),
+            }
+        })
+        .filter(|a| match query.filter {
+            None => true,
+            Some(TodoFilter::Upcoming) => a.state == TodoState::NotSubmitted,
+            Some(TodoFilter::Overdue) => a.state == TodoState::Missing,
+            Some(TodoFilter::Done) => matches!(a.state, TodoState::TurnedIn | TodoState::Graded),
+        })
+        .collect();
+
+    Ok(Json(todo))
 }
@@ -9,6 +9,22 @@ mod dto;
 async fn main() {
     dotenv::dotenv().ok();
 
+    // `cargo run -- grant-admin <email>` lets an already registered account run OneRoster imports
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, email] = args.as_slice() && command == "grant-admin" {
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = db::create_pool(&database_url).await;
+
+        match db::grant_admin(&pool, email).await.expect("Failed to grant admin rights") {
+            Some(user_id) => println!("Granted admin rights to {} (user {})", email, user_id),
+            None => {
+                eprintln!("No account is registered with {}", email);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let app = routes::make_app()
         .await
         .expect("Failed to create app");
@@ -22,7 +22,7 @@ pub fn create_jwt(user_id: i32, classroom_roles: Vec<UserClassroomRole>) -> Resu
         .timestamp();
     
     let claims = Claims {
-        sub: user_id as i32,
+        sub: user_id,
         exp: expiration as usize,
         iat: Utc::now().timestamp() as usize,
         classroom_roles: classroom_roles.clone(),
@@ -11,6 +11,8 @@ pub struct User {
     pub email: String,
     #[serde(skip_serializing)]
     pub password_hash: String,
+    pub sourced_id: Option<String>,
+    pub is_admin: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -23,6 +25,21 @@ pub enum ClassroomRole {
     Student,
 }
 
+impl ClassroomRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClassroomRole::Creator => "creator",
+            ClassroomRole::Teacher => "teacher",
+            ClassroomRole::Student => "student",
+        }
+    }
+
+    // the creator is a teacher too, with the extra right to manage other members' roles
+    pub fn is_teacher(&self) -> bool {
+        matches!(self, ClassroomRole::Creator | ClassroomRole::Teacher)
+    }
+}
+
 impl From<String> for ClassroomRole {
     fn from(role: String) -> Self {
         match role.as_str() {
@@ -34,6 +51,104 @@ impl From<String> for ClassroomRole {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "submission_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    Assigned,
+    TurnedIn,
+    Returned,
+    Resubmitted,
+}
+
+impl SubmissionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubmissionStatus::Assigned => "assigned",
+            SubmissionStatus::TurnedIn => "turned_in",
+            SubmissionStatus::Returned => "returned",
+            SubmissionStatus::Resubmitted => "resubmitted",
+        }
+    }
+
+    pub fn is_turned_in(&self) -> bool {
+        !matches!(self, SubmissionStatus::Assigned)
+    }
+
+    pub fn turn_in(self) -> SubmissionStatus {
+        match self {
+            SubmissionStatus::Assigned | SubmissionStatus::TurnedIn => SubmissionStatus::TurnedIn,
+            SubmissionStatus::Returned | SubmissionStatus::Resubmitted => SubmissionStatus::Resubmitted,
+        }
+    }
+
+    pub fn unsubmit(self) -> Result<SubmissionStatus, &'static str> {
+        match self {
+            SubmissionStatus::TurnedIn => Ok(SubmissionStatus::Assigned),
+            SubmissionStatus::Resubmitted => Ok(SubmissionStatus::Returned),
+            SubmissionStatus::Assigned => Err("Submission is not turned in"),
+            SubmissionStatus::Returned => Err("Returned submissions cannot be unsubmitted"),
+        }
+    }
+
+    pub fn cancel_grade(self, has_submitted_work: bool) -> Result<SubmissionStatus, &'static str> {
+        match self {
+            SubmissionStatus::Returned if has_submitted_work => Ok(SubmissionStatus::TurnedIn),
+            SubmissionStatus::Returned => Ok(SubmissionStatus::Assigned),
+            _ => Err("Submission is not graded"),
+        }
+    }
+
+    pub fn is_late(&self, submitted_at: Option<DateTime<Utc>>, due_date: Option<DateTime<Utc>>) -> bool {
+        self.is_turned_in()
+            && matches!((submitted_at, due_date), (Some(submitted_at), Some(due_date)) if submitted_at > due_date)
+    }
+
+    pub fn is_missing(&self, due_date: Option<DateTime<Utc>>) -> bool {
+        !self.is_turned_in() && due_date.is_some_and(|due_date| due_date < Utc::now())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "invitation_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Revoked,
+}
+
+impl InvitationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvitationStatus::Pending => "pending",
+            InvitationStatus::Accepted => "accepted",
+            InvitationStatus::Declined => "declined",
+            InvitationStatus::Revoked => "revoked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "enrollment_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EnrollmentStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl EnrollmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnrollmentStatus::Pending => "pending",
+            EnrollmentStatus::Approved => "approved",
+            EnrollmentStatus::Rejected => "rejected",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
 pub struct UserClassroomRole {
     pub user_id: i32,
@@ -43,6 +158,7 @@ pub struct UserClassroomRole {
 }
 
 #[derive(Serialize, Deserialize, FromRow, Debug)]
+#[allow(dead_code)]
 pub struct Submission {
     pub id: i32,
     pub assignment_id: i32,
@@ -50,25 +166,90 @@ pub struct Submission {
     pub file_drive_id: String,
     pub file_name: String,
     pub grade: Option<i32>,
+    pub raw_grade: Option<i32>,
     pub feedback: Option<String>,
+    pub status: SubmissionStatus,
     pub submitted_at: DateTime<Utc>,
     pub graded_at: Option<DateTime<Utc>>,
     pub graded_by: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize, FromRow, Debug)]
+#[allow(dead_code)]
 pub struct Classroom {
     pub id: i32,
     pub id_base64: String,
     pub name: String,
     pub description: String,
     pub join_code: String,
+    pub join_code_enabled: bool,
+    pub require_approval: bool,
+    pub sourced_id: Option<String>,
+    pub org_sourced_id: Option<String>,
     pub creator_id: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+// no 0/O or 1/I so codes can be read out loud; 32 symbols keep `byte % 32` unbiased
+const JOIN_CODE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+pub const JOIN_CODE_LENGTH: usize = 7;
+
+pub fn generate_join_code() -> String {
+    // bytes 6 and 8 of a v4 UUID carry the version and variant bits, 9..16 are fully random
+    uuid::Uuid::new_v4().as_bytes()[9..9 + JOIN_CODE_LENGTH]
+        .iter()
+        .map(|b| JOIN_CODE_ALPHABET[(*b % 32) as usize] as char)
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct InviteLink {
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_uses: Option<i32>,
+    pub use_count: i32,
+    // lowercased; an empty list together with no domain means anyone with the link may join
+    pub allowed_emails: Vec<String>,
+    pub allowed_domain: Option<String>,
+    pub revoked: bool,
+}
+
+impl InviteLink {
+    pub fn is_used_up(&self) -> bool {
+        self.max_uses.is_some_and(|max_uses| self.use_count >= max_uses)
+    }
+
+    pub fn allows_email(&self, email: &str) -> bool {
+        if self.allowed_emails.is_empty() && self.allowed_domain.is_none() {
+            return true;
+        }
+        let email = email.trim().to_lowercase();
+        let domain = email.rsplit_once('@').map(|(_, domain)| domain);
+
+        self.allowed_emails.contains(&email)
+            || matches!((&self.allowed_domain, domain), (Some(allowed), Some(domain)) if allowed == domain)
+    }
+
+    pub fn check(&self, email: &str, at: DateTime<Utc>) -> Result<(), &'static str> {
+        if self.revoked {
+            return Err("This invite link has been revoked");
+        }
+        if self.expires_at.is_some_and(|expires_at| at > expires_at) {
+            return Err("This invite link has expired");
+        }
+        if self.is_used_up() {
+            return Err("This invite link has reached its maximum number of uses");
+        }
+        if !self.allows_email(email) {
+            return Err("This invite link is not valid for your email address");
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, FromRow, Debug)]
+#[allow(dead_code)]
 pub struct Assignment {
     pub id: i32,
     pub id_base64: String,
@@ -77,7 +258,168 @@ pub struct Assignment {
     pub description: String,
     pub due_date: DateTime<Utc>,
     pub points: i32,
+    pub position: i32,
+    pub category_id: Option<i32>,
+    pub accept_late: bool,
+    pub late_penalty_per_day: i32,
+    pub late_cutoff: Option<DateTime<Utc>>,
     pub created_by: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LatePolicy {
+    pub due_date: Option<DateTime<Utc>>,
+    pub accept_late: bool,
+    // percent of the raw grade deducted for every started day after the due date
+    pub penalty_per_day: i32,
+    pub cutoff: Option<DateTime<Utc>>,
+}
+
+impl LatePolicy {
+    pub fn check_submission(&self, at: DateTime<Utc>) -> Result<(), &'static str> {
+        if self.cutoff.is_some_and(|cutoff| at > cutoff) {
+            return Err("The submission cutoff for this assignment has passed");
+        }
+        if !self.accept_late && self.due_date.is_some_and(|due_date| at > due_date) {
+            return Err("Late submissions are not accepted for this assignment");
+        }
+        Ok(())
+    }
+
+    pub fn with_extension(
+        self,
+        extended_due_date: Option<DateTime<Utc>>,
+        extended_cutoff: Option<DateTime<Utc>>,
+    ) -> LatePolicy {
+        let due_date = extended_due_date.or(self.due_date);
+        // a class-wide cutoff must never end before the student's own deadline
+        let cutoff = extended_cutoff.or(match (self.cutoff, due_date) {
+            (Some(cutoff), Some(due_date)) => Some(cutoff.max(due_date)),
+            (cutoff, _) => cutoff,
+        });
+
+        LatePolicy {
+            due_date,
+            cutoff,
+            ..self
+        }
+    }
+
+    pub fn days_late(&self, submitted_at: Option<DateTime<Utc>>) -> i64 {
+        match (submitted_at, self.due_date) {
+            (Some(submitted_at), Some(due_date)) if submitted_at > due_date => {
+                let seconds = (submitted_at - due_date).num_seconds();
+                (seconds + 86_399) / 86_400
+            }
+            _ => 0,
+        }
+    }
+
+    pub fn apply_penalty(&self, raw_grade: i32, submitted_at: Option<DateTime<Utc>>) -> i32 {
+        let deduction = (self.days_late(submitted_at) * self.penalty_per_day as i64).clamp(0, 100);
+        (raw_grade as f64 * (100 - deduction) as f64 / 100.0).round() as i32
+    }
+}
+
+#[derive(Serialize, Deserialize, FromRow, Debug, Clone)]
+pub struct GradingCategory {
+    pub id: i32,
+    pub classroom_id: i32,
+    pub name: String,
+    pub weight: f64,
+    pub drop_lowest: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScoredAssignment {
+    pub category_id: Option<i32>,
+    pub grade: i32,
+    pub points: i32,
+}
+
+impl GradingCategory {
+    pub fn percentage(&self, scores: &[ScoredAssignment]) -> Option<f64> {
+        let mut scores: Vec<&ScoredAssignment> = scores
+            .iter()
+            .filter(|s| s.category_id == Some(self.id))
+            .collect();
+
+        // drop the lowest ratios, but always keep at least one graded assignment
+        let drop = (self.drop_lowest.max(0) as usize).min(scores.len().saturating_sub(1));
+        scores.sort_by(|a, b| score_ratio(a).total_cmp(&score_ratio(b)));
+        let kept = &scores[drop..];
+
+        let earned: i64 = kept.iter().map(|s| s.grade as i64).sum();
+        let possible: i64 = kept.iter().map(|s| s.points as i64).sum();
+
+        (possible > 0).then(|| 100.0 * earned as f64 / possible as f64)
+    }
+}
+
+fn score_ratio(score: &ScoredAssignment) -> f64 {
+    if score.points > 0 {
+        score.grade as f64 / score.points as f64
+    } else {
+        f64::INFINITY
+    }
+}
+
+// categories without any graded work are left out and the remaining weights are rescaled
+pub fn weighted_percentage(categories: &[GradingCategory], scores: &[ScoredAssignment]) -> Option<f64> {
+    let (weighted_sum, total_weight) = categories
+        .iter()
+        .filter_map(|c| c.percentage(scores).map(|p| (p * c.weight, c.weight)))
+        .fold((0.0, 0.0), |(sum, total), (p, w)| (sum + p, total + w));
+
+    (total_weight > 0.0).then(|| weighted_sum / total_weight)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GradeBand {
+    pub label: String,
+    pub min_percentage: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct GradingScale {
+    pub bands: Vec<GradeBand>,
+}
+
+impl Default for GradingScale {
+    fn default() -> Self {
+        let bands = [("A", 90.0), ("B", 80.0), ("C", 70.0), ("D", 60.0), ("F", 0.0)]
+            .into_iter()
+            .map(|(label, min_percentage)| GradeBand {
+                label: label.to_string(),
+                min_percentage,
+            })
+            .collect();
+        GradingScale { bands }
+    }
+}
+
+impl GradingScale {
+    pub fn new(mut bands: Vec<GradeBand>) -> Self {
+        bands.sort_by(|a, b| b.min_percentage.total_cmp(&a.min_percentage));
+        GradingScale { bands }
+    }
+
+    pub fn label_for(&self, percentage: f64) -> Option<String> {
+        self.bands
+            .iter()
+            .find(|b| percentage >= b.min_percentage)
+            .map(|b| b.label.clone())
+    }
+
+    pub fn display_grade(&self, grade: Option<i32>, points: i32) -> Option<String> {
+        let grade = grade?;
+        if points <= 0 {
+            return None;
+        }
+        self.label_for(100.0 * grade as f64 / points as f64)
+    }
+}
@@ -38,24 +38,72 @@ pub async fn make_app() -> Result<Router, Box<dyn Error>> {
         .route("/api/auth/register", post(handlers::auth::register))
         .route("/api/auth/login", post(handlers::auth::login))
         .route("/api/auth/logout", post(handlers::auth::logout))
+        .route("/api/auth/set-password", post(handlers::auth::set_password))
         .route("/api/user/profile", get(handlers::user::profile))
         .route("/api/user/profile/edit", post(handlers::user::edit_profile))
         .route("/api/user", get(handlers::user::get_user))
         .route("/api/user/classes", get(handlers::user::list_classes))
+        .route("/api/user/classes/archived", get(handlers::user::list_archived_classes))
+        .route("/api/user/grades", get(handlers::user::list_grades))
+        .route("/api/user/assignments", get(handlers::user::list_assignments))
+        .route("/api/user/invitations", get(handlers::members::list_my_invitations))
+        .route("/api/user/invitations/{inv_id}/accept", post(handlers::members::accept_invitation))
+        .route("/api/user/invitations/{inv_id}/decline", post(handlers::members::decline_invitation))
+        .route("/api/user/enrollment-requests", get(handlers::members::list_my_enrollment_requests))
+        .route("/api/oneroster/import", post(handlers::oneroster::import_oneroster))
         .route("/api/class/create", post(handlers::classroom::create_class))
         .route("/api/class/join", post(handlers::classroom::join_class))
-        .route("/api/class/{id}", get(handlers::classroom::get_class))
+        .route("/api/class/{id}", get(handlers::classroom::get_class).put(handlers::classroom::update_class).delete(handlers::classroom::delete_class))
         .route("/api/class/{id}/role", get(handlers::classroom::get_class_role))
+        .route("/api/class/{id}/join-code", put(handlers::classroom::update_join_code))
+        .route("/api/class/{id}/join-code/regenerate", post(handlers::classroom::regenerate_join_code))
+        .route("/api/class/{id}/archive", post(handlers::classroom::archive_class))
+        .route("/api/class/{id}/unarchive", post(handlers::classroom::unarchive_class))
         .route("/api/class/{id}/create-assignment", post(handlers::classroom::create_assignment))
-        .route("/api/class/{c_id}/assignment/{a_id}", get(handlers::classroom::get_assignment))
+        .route("/api/class/{id}/assignments/order", put(handlers::classroom::reorder_assignments))
+        .route("/api/class/{c_id}/categories", get(handlers::classroom::list_categories).post(handlers::classroom::create_category))
+        .route("/api/class/{c_id}/categories/{cat_id}", put(handlers::classroom::update_category).delete(handlers::classroom::delete_category))
+        .route("/api/class/{c_id}/grading-scale", get(handlers::classroom::get_grading_scale).put(handlers::classroom::update_grading_scale))
+        .route("/api/class/{c_id}/invitations", get(handlers::members::list_invitations).post(handlers::members::invite_teacher))
+        .route("/api/class/{c_id}/invitations/{inv_id}", delete(handlers::members::revoke_invitation))
+        .route("/api/class/{c_id}/invite-links", get(handlers::members::list_invite_links).post(handlers::members::create_invite_link))
+        .route("/api/class/{c_id}/invite-links/{link_id}", delete(handlers::members::revoke_invite_link))
+        .route("/api/class/{c_id}/enrollment-requests", get(handlers::members::list_enrollment_requests))
+        .route("/api/class/{c_id}/enrollment-requests/{req_id}/approve", post(handlers::members::approve_enrollment_request))
+        .route("/api/class/{c_id}/enrollment-requests/{req_id}/reject", post(handlers::members::reject_enrollment_request))
+        .route("/api/class/{c_id}/groups", get(handlers::groups::list_groups).post(handlers::groups::create_group))
+        .route("/api/class/{c_id}/groups/{g_id}", put(handlers::groups::update_group).delete(handlers::groups::delete_group))
+        .route("/api/class/{c_id}/groups/{g_id}/members", post(handlers::groups::add_group_members))
+        .route("/api/class/{c_id}/groups/{g_id}/members/{u_id}", delete(handlers::groups::remove_group_member))
+        .route("/api/class/{c_id}/roster/import", post(handlers::roster::import_roster))
+        .route("/api/class/{c_id}/roster/export", get(handlers::roster::export_roster))
+        .route("/api/class/{c_id}/oneroster/export", get(handlers::oneroster::export_oneroster))
+        .route("/api/class/{c_id}/members/{u_id}", delete(handlers::members::remove_member))
+        .route("/api/class/{c_id}/members/{u_id}/role", put(handlers::members::update_member_role))
+        .route("/api/class/{c_id}/leave", post(handlers::members::leave_class))
+        .route("/api/class/{c_id}/stream", get(handlers::stream::get_stream))
+        .route("/api/class/{c_id}/announcements", post(handlers::stream::create_announcement))
+        .route("/api/class/{c_id}/announcements/{an_id}", put(handlers::stream::update_announcement).delete(handlers::stream::delete_announcement))
+        .route("/api/class/{c_id}/announcements/{an_id}/attachments", post(handlers::stream::add_announcement_attachments))
+        .route("/api/class/{c_id}/announcements/{an_id}/comments", post(handlers::stream::create_comment))
+        .route("/api/class/{c_id}/announcements/{an_id}/comments/{cm_id}", delete(handlers::stream::delete_comment))
+        .route("/api/class/{c_id}/download-announcement-file/{f_id}", get(handlers::stream::download_announcement_file))
+        .route("/api/class/{c_id}/assignment/{a_id}", get(handlers::classroom::get_assignment).put(handlers::classroom::update_assignment).delete(handlers::classroom::delete_assignment))
+        .route("/api/class/{c_id}/assignment/{a_id}/extensions", get(handlers::classroom::list_extensions))
+        .route("/api/class/{c_id}/assignment/{a_id}/extensions/{u_id}", put(handlers::classroom::grant_extension).delete(handlers::classroom::revoke_extension))
+        .route("/api/class/{c_id}/assignment/{a_id}/rubric", put(handlers::classroom::update_rubric).delete(handlers::classroom::delete_rubric))
         .route("/api/class/{c_id}/assignment/{a_id}/add-materials", post(handlers::classroom::add_assignment_materials))
         .route("/api/class/{c_id}/assignment/{a_id}/submit", post(handlers::classroom::save_submission_multipart_files))
         .route("/api/class/{c_id}/assignment/{a_id}/delete-file/{f_id}", delete(handlers::classroom::delete_assignment_file))
         .route("/api/class/{c_id}/assignment/{a_id}/cancel-submission", delete(handlers::classroom::cancel_submission))
         .route("/api/class/{c_id}/submissions", get(handlers::classroom::list_submissions))
+        .route("/api/class/{c_id}/gradebook", get(handlers::classroom::get_gradebook))
+        .route("/api/class/{c_id}/gradebook/export", get(handlers::classroom::export_gradebook))
+        .route("/api/class/{c_id}/gradebook/import", post(handlers::classroom::import_grades))
         .route("/api/class/{c_id}/submissions/{s_id}", get(handlers::classroom::get_submission))
         .route("/api/class/{c_id}/submissions/{s_id}/grade", post(handlers::classroom::grade_submission))
         .route("/api/class/{c_id}/submissions/{s_id}/cancel-grade", put(handlers::classroom::cancel_grade))
+        .route("/api/class/{c_id}/submissions/{s_id}/comments", get(handlers::classroom::list_submission_comments).post(handlers::classroom::create_submission_comment))
         .route("/api/class/{c_id}/download-submission-file/{f_id}", get(handlers::classroom::download_submission_file))
         .route("/api/class/{c_id}/download-material-file/{f_id}", get(handlers::classroom::download_material_file))
         .layer(cors)